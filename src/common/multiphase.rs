@@ -30,16 +30,43 @@ multiphase_string_types![Identifier, Shebang, SylanString, SyDoc];
 
 /// Interpolations are interleaved with string fragments, ready to be glued
 /// together when the runtime knows what the interpolated identifiers resolve
-/// to.
+/// to. There is always one more fragment than interpolations, with empty ones
+/// where interpolations are adjacent or at either end, so that the fragment
+/// at each index comes directly before the interpolation at the same index.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct InterpolatedString {
     pub string_fragments: Vec<String>,
     pub interpolations: Vec<Identifier>,
 }
 
+/// A number's whole and fractional parts, followed by how many digits the
+/// fractional part was written with. Both parts carry the number's sign, so
+/// that numbers between zero and minus one, such as `-0.5` being
+/// `Number(0, -5, 1)`, don't lose it. The digit count keeps the fractional
+/// part's leading zeros, so that `0.05` is `Number(0, 5, 2)` rather than being
+/// indistinguishable from `0.5`.
 // TODO: implement properly with a multiprecision library.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct Number(pub i64, pub u64);
+pub struct Number(pub i64, pub i64, pub u32);
+
+impl Number {
+    pub fn negate(self) -> Number {
+        let Number(real, fractional, fractional_digits) = self;
+        Number(-real, -fractional, fractional_digits)
+    }
+
+    /// Drop the fractional part's trailing zeros, which don't change the
+    /// number, so that `1.50` is `Number(1, 5, 1)` just like `1.5`, and `1.0`
+    /// is the same as `1`.
+    pub fn without_trailing_zeros(self) -> Number {
+        let Number(real, mut fractional, mut fractional_digits) = self;
+        while (0 < fractional_digits) && (fractional % 10 == 0) {
+            fractional /= 10;
+            fractional_digits -= 1;
+        }
+        Number(real, fractional, fractional_digits)
+    }
+}
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Accessibility {
@@ -58,7 +85,9 @@ pub enum Accessibility {
 pub enum OverloadableInfixOperator {
     Add,
 
-    // Can be either a type constrait union or a bitwise and. Work it out in the parser.
+    // Can be either a type constrait union or a bitwise and. The parser works
+    // it out from position: after a type parameter's `extends`, it always
+    // joins bounds.
     Ampersand,
 
     And,
@@ -222,3 +251,16 @@ pub enum PseudoIdentifier {
     /// transforms an invocation into a partial application.
     PlaceholderIdentifier,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negate() {
+        assert_eq!(Number(5, 0, 0).negate(), Number(-5, 0, 0));
+        assert_eq!(Number(-1, -25, 2).negate(), Number(1, 25, 2));
+        assert_eq!(Number(0, 5, 1).negate(), Number(0, -5, 1));
+        assert_eq!(Number(0, 0, 0).negate(), Number(0, 0, 0));
+    }
+}
@@ -1,8 +1,37 @@
 /// Versioning across Sylan is done consistently with [Semantic Versioning](https://semver.org), aka
 /// "semver".
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+///
+/// Versions order numerically by major, then minor, then patch, which the
+/// derived ordering gives by the field order. Semver's prerelease tags would
+/// sort before their release once added, so they must go after `patch` and
+/// not be compared naively.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub struct Version {
     pub major: u64,
     pub minor: u64,
     pub patch: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(major: u64, minor: u64, patch: u64) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    #[test]
+    fn numeric_ordering() {
+        assert!(version(1, 2, 0) < version(1, 10, 0));
+        assert!(version(2, 0, 0) > version(1, 99, 99));
+        assert!(version(1, 0, 9) < version(1, 0, 10));
+        assert_eq!(
+            version(1, 2, 3).cmp(&version(1, 2, 3)),
+            std::cmp::Ordering::Equal
+        );
+    }
+}
@@ -13,11 +13,14 @@
 //! task is then hidden behind a `PeekableBuffer`. This allows consumers to treat it as a buffer
 //! without even considering the concurrency that backs the implementation.
 
+use std::collections::VecDeque;
 use std::io;
 use std::ops::Index;
 
 use crate::common::peekable_buffer::PeekableBuffer;
-use crate::lexing::lexer::{LexedToken, Lexer, LexerTask, LexerTaskError};
+use crate::lexing::lexer::{Error, LexedToken, Lexer, LexerTask, LexerTaskError};
+use crate::lexing::tokens::Token;
+use crate::source::Position;
 
 mod char_escapes;
 mod keywords;
@@ -26,32 +29,112 @@ mod non_word_chars;
 pub mod lexer;
 pub mod tokens;
 
-const MAX_TOKEN_LOOKAHEAD: usize = 5;
-
 pub struct Tokens {
-    lookahead: [LexedToken; MAX_TOKEN_LOOKAHEAD],
-    lookahead_len: usize,
+    /// Tokens peeked at but not yet read. It grows to however far ahead the
+    /// parser looks, although that is rarely more than a few tokens.
+    lookahead: VecDeque<LexedToken>,
+
     lexer_task: LexerTask,
+
+    /// The lexer stops after sending `Token::Eof`, so nothing more is received
+    /// after it.
+    reached_eof: bool,
+
+    /// Where the `Token::Eof` was lexed, once it has been received, so that
+    /// errors about the source ending early can point at its true end.
+    eof_position: Option<Position>,
+
+    /// Where the most recently read or discarded token ended, so that the
+    /// parser can tell where what it just parsed finishes.
+    last_end: Option<Position>,
+
+    /// The lexer also stops after sending an error, which is kept here for the
+    /// parser to report in place of the stream just ending.
+    lexer_error: Option<Error>,
 }
 
 impl Tokens {
     pub fn from(lexer: Lexer) -> io::Result<Self> {
         lexer.lex().map(|lexer_task| Self {
-            lookahead: [
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                Default::default(),
-                Default::default(),
-            ],
-            lookahead_len: 0,
+            lookahead: VecDeque::new(),
             lexer_task,
+            reached_eof: false,
+            eof_position: None,
+            last_end: None,
+            lexer_error: None,
         })
     }
 
+    fn recv(&mut self) -> Option<LexedToken> {
+        if self.reached_eof {
+            None
+        } else {
+            match self.lexer_task.recv().ok()? {
+                Ok(lexed) => {
+                    self.reached_eof = lexed.token == Token::Eof;
+                    if self.reached_eof {
+                        self.eof_position = Some(lexed.position);
+                    }
+                    Some(lexed)
+                }
+                Err(err) => {
+                    self.reached_eof = true;
+                    self.lexer_error = Some(err);
+                    None
+                }
+            }
+        }
+    }
+
+    /// The error that ended the token stream early, if the lexer hit one.
+    pub fn lexer_error(&self) -> Option<&Error> {
+        self.lexer_error.as_ref()
+    }
+
+    /// The position of the end of the source, if the stream has reached it.
+    pub fn eof_position(&self) -> Option<Position> {
+        self.eof_position
+    }
+
+    /// The end of the last token read or discarded, if any has been yet.
+    pub fn last_end(&self) -> Option<Position> {
+        self.last_end
+    }
+
     pub fn join_lexer_thread(self) -> Result<(), LexerTaskError> {
         self.lexer_task.join()
     }
+
+    /// Read every remaining token up to and including the `Token::Eof`, and
+    /// then join the lexer thread to surface any error it hit.
+    pub fn collect_all(mut self) -> Result<Vec<LexedToken>, LexerTaskError> {
+        let tokens = self.by_ref().collect();
+        self.join_lexer_thread().map(|()| tokens)
+    }
+}
+
+/// Iterating reads the remaining tokens, ending after the `Token::Eof`. Iterate
+/// via `by_ref` to join the lexer thread afterwards.
+impl Iterator for Tokens {
+    type Item = LexedToken;
+
+    fn next(&mut self) -> Option<LexedToken> {
+        self.read()
+    }
+}
+
+/// Put the source back together from its tokens, byte-for-byte, by writing
+/// out each token's trivia followed by its text. This is the baseline that
+/// formatters and refactoring tools work from.
+pub fn reconstruct_source(tokens: &[LexedToken]) -> String {
+    let mut source = String::new();
+    for lexed in tokens {
+        if let Some(trivia) = &lexed.trivia {
+            source.push_str(trivia);
+        }
+        source.push_str(&lexed.text);
+    }
+    source
 }
 
 pub struct LexedTokenReadMany(Vec<LexedToken>);
@@ -67,93 +150,49 @@ impl Index<usize> for LexedTokenReadMany {
 
 impl<'a> PeekableBuffer<'a, LexedToken, LexedTokenReadMany> for Tokens {
     fn peek_many(&mut self, n: usize) -> Option<&[LexedToken]> {
-        let lexer = &self.lexer_task;
-
-        // Expand and the lookahead if it's not big enough.
-        let pending_peeks = n - self.lookahead_len;
-        let mut n = self.lookahead_len;
-        let m = self.lookahead_len + pending_peeks;
-        let ok = loop {
-            if m <= n {
-                break true;
-            }
-            self.lookahead[n] = match lexer.recv() {
-                Ok(token) => token,
-                Err(_) => break false,
-            };
-            n += 1;
-        };
-        self.lookahead_len += pending_peeks;
-
-        if ok {
-            // The lookahead now covers the range requested, so slice it.
-            Some(&self.lookahead[..(self.lookahead_len)])
-        } else {
-            None
+        // Expand the lookahead if it's not big enough.
+        while self.lookahead.len() < n {
+            let token = self.recv()?;
+            self.lookahead.push_back(token);
         }
+
+        // The lookahead now covers the range requested, so slice it.
+        Some(&self.lookahead.make_contiguous()[..n])
     }
 
     fn read_many(&mut self, n: usize) -> Option<LexedTokenReadMany> {
-        let lookahead_to_consume = self.lookahead_len.min(n);
-        let mut non_lookahead_to_consume = n - lookahead_to_consume;
-
-        // First consume the lookahead.
-        let mut read_tokens = (0..lookahead_to_consume)
-            .zip(lookahead_to_consume..(lookahead_to_consume + self.lookahead_len))
-            .enumerate()
-            .map(|(i, (destination, source))| {
-                // TODO: work out how to do a `swap_remove` on a slice to avoid
-                // a heap allocation and copying the already allocated string in
-                // the lexed token.
-                let token = self.lookahead[i].clone();
-
-                self.lookahead.swap(destination, source);
-                token
-            })
-            .collect::<Vec<LexedToken>>();
-        self.lookahead_len -= lookahead_to_consume;
+        let lookahead_to_consume = self.lookahead.len().min(n);
 
-        // Having exhausted the lookahead, the remaining reads are from the
-        // token channel.
-        let ok = loop {
-            if non_lookahead_to_consume == 0 {
-                break true;
-            }
-            match self.lexer_task.recv() {
-                Ok(token) => read_tokens.push(token),
-                Err(_) => break false,
-            }
-            non_lookahead_to_consume -= 1;
-        };
+        // First consume the lookahead, and then read the rest from the token
+        // channel.
+        let mut read_tokens = self
+            .lookahead
+            .drain(..lookahead_to_consume)
+            .collect::<Vec<LexedToken>>();
+        while read_tokens.len() < n {
+            read_tokens.push(self.recv()?);
+        }
 
-        if ok {
-            Some(LexedTokenReadMany(read_tokens))
-        } else {
-            None
+        if let Some(last) = read_tokens.last() {
+            self.last_end = Some(last.end);
         }
+        Some(LexedTokenReadMany(read_tokens))
     }
 
     fn discard_many(&mut self, n: usize) -> bool {
-        let lookahead_to_discard = self.lookahead_len.min(n);
-        let mut non_lookahead_to_discard = -((self.lookahead_len as isize) - (n as isize));
-
-        // First discard the lookahead.
-        (0..lookahead_to_discard)
-            .zip(lookahead_to_discard..(lookahead_to_discard + self.lookahead_len))
-            .for_each(|(destination, source)| self.lookahead.swap(destination, source));
-        self.lookahead_len -= lookahead_to_discard;
+        let lookahead_to_discard = self.lookahead.len().min(n);
+        if let Some(last) = self.lookahead.drain(..lookahead_to_discard).next_back() {
+            self.last_end = Some(last.end);
+        }
 
         // Now the lookahead is consumed, discard from the token channel.
-        loop {
-            if non_lookahead_to_discard <= 0 {
-                break true;
+        (lookahead_to_discard..n).all(|_| match self.recv() {
+            Some(lexed) => {
+                self.last_end = Some(lexed.end);
+                true
             }
-            match self.lexer_task.recv() {
-                Ok(_) => {}
-                Err(_) => break false,
-            }
-            non_lookahead_to_discard -= 1;
-        }
+            None => false,
+        })
     }
 }
 
@@ -161,7 +200,7 @@ impl<'a> PeekableBuffer<'a, LexedToken, LexedTokenReadMany> for Tokens {
 mod tests {
     use std::fmt::Debug;
 
-    use crate::common::multiphase::{Identifier, Number};
+    use crate::common::multiphase::{Identifier, Number, OverloadableInfixOperator};
     use crate::lexing::tokens::{Grouping, Literal, Token};
     use crate::source::in_memory::Source;
 
@@ -216,18 +255,39 @@ mod tests {
             &vec![
                 Token::Identifier(Identifier::from("List")),
                 Token::Grouping(Grouping::OpenParentheses),
-                Token::Literal(Literal::Number(Number(1, 0))),
+                Token::Literal(Literal::Number(Number(1, 0, 0))),
                 Token::SubItemSeparator,
             ],
         )
     }
 
+    #[test]
+    fn deep_lookahead() {
+        test(|tokens| {
+            assert_eq!(
+                tokens.peek_nth(12).map(|lexed| lexed.token.clone()),
+                Some(Token::OverloadableInfixOperator(
+                    OverloadableInfixOperator::Cascade
+                )),
+            );
+            assert_eq!(tokens.peek_many(1000), None);
+
+            // Peeking past the end of the stream leaves what was peeked to be
+            // read as normal.
+            let read = (0..13)
+                .map(|_| tokens.read().unwrap().token)
+                .collect::<Vec<Token>>();
+            assert_eq!(read[0], Token::Identifier(Identifier::from("List")));
+            assert_eq!(read[9], Token::Identifier(Identifier::from("forEach")));
+        });
+    }
+
     #[test]
     fn peek_nth() {
         assert_next(
             |tokens| {
                 tokens.discard_many(5);
-                tokens.peek_nth(5).unwrap().token.clone()
+                tokens.peek_nth(4).unwrap().token.clone()
             },
             &Token::Identifier(Identifier::from("forEach")),
         );
@@ -242,7 +302,7 @@ mod tests {
                 tokens.peek().unwrap();
                 tokens.read().unwrap().token
             },
-            &Token::Literal(Literal::Number(Number(1, 0))),
+            &Token::Literal(Literal::Number(Number(1, 0, 0))),
         )
     }
 
@@ -274,7 +334,7 @@ mod tests {
                 tokens.discard();
                 tokens.read().unwrap().token
             },
-            &Token::Literal(Literal::Number(Number(2, 0))),
+            &Token::Literal(Literal::Number(Number(2, 0, 0))),
         )
     }
 
@@ -292,11 +352,55 @@ mod tests {
     #[test]
     fn match_nth() {
         test(|tokens| {
-            assert!(tokens.match_nth(3, |lexed| lexed.token
-                == Token::Literal(Literal::Number(Number(1, 0)))))
+            assert!(tokens.match_nth(2, |lexed| lexed.token
+                == Token::Literal(Literal::Number(Number(1, 0, 0)))))
         })
     }
 
+    #[test]
+    fn reading_past_eof() {
+        let source = Source::from("a".chars().collect::<Vec<char>>());
+        let mut tokens = Tokens::from(Lexer::from(source)).unwrap();
+
+        assert_eq!(
+            tokens.read().unwrap().token,
+            Token::Identifier(Identifier::from("a")),
+        );
+        assert_eq!(tokens.peek().unwrap().token, Token::Eof);
+        assert!(tokens.peek_many(2).is_none());
+        assert_eq!(tokens.read().unwrap().token, Token::Eof);
+        assert!(tokens.read().is_none());
+        assert!(tokens.read().is_none());
+        assert!(tokens.read_many(3).is_none());
+        assert!(!tokens.discard_many(2));
+        assert!(tokens.peek().is_none());
+
+        tokens.lexer_task.join().unwrap();
+    }
+
+    #[test]
+    fn collect_all() {
+        let source = Source::from("List(1)".chars().collect::<Vec<char>>());
+        let tokens = Tokens::from(Lexer::from(source))
+            .unwrap()
+            .collect_all()
+            .unwrap()
+            .into_iter()
+            .map(|lexed| lexed.token)
+            .collect::<Vec<Token>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier(Identifier::from("List")),
+                Token::Grouping(Grouping::OpenParentheses),
+                Token::Literal(Literal::Number(Number(1, 0, 0))),
+                Token::Grouping(Grouping::CloseParentheses),
+                Token::Eof,
+            ],
+        );
+    }
+
     #[test]
     fn trivia() {
         let trivia_to_match = String::from(
@@ -309,4 +413,66 @@ mod tests {
             &trivia_to_match,
         );
     }
+
+    #[test]
+    fn iterating() {
+        let tokens = |source: &str| {
+            let source = Source::from(source.chars().collect::<Vec<char>>());
+            Tokens::from(Lexer::from(source)).unwrap()
+        };
+
+        let mut read = tokens("List(1)");
+        let mut expected = vec![];
+        while let Some(lexed) = read.read() {
+            expected.push(lexed);
+        }
+        read.join_lexer_thread().unwrap();
+
+        let mut iterated = tokens("List(1)");
+        let actual = iterated.by_ref().collect::<Vec<LexedToken>>();
+        iterated.join_lexer_thread().unwrap();
+
+        assert_eq!(actual, expected);
+        assert_eq!(actual.last().map(|lexed| &lexed.token), Some(&Token::Eof));
+    }
+
+    #[test]
+    fn joining_after_stopping_early() {
+        let source = "a + ".repeat(100_000);
+        let source = Source::from(source.chars().collect::<Vec<char>>());
+        let mut tokens = Tokens::from(Lexer::from(source)).unwrap();
+
+        assert_eq!(
+            tokens.read().map(|lexed| lexed.token),
+            Some(Token::Identifier(Identifier::from("a"))),
+        );
+        tokens.join_lexer_thread().unwrap();
+    }
+
+    #[test]
+    fn lexing_synchronously() {
+        let lexer = || Lexer::from(Source::from(TEST_SOURCE.chars().collect::<Vec<char>>()));
+
+        let threaded = Tokens::from(lexer()).unwrap().collect_all().unwrap();
+        let synchronous = lexer().lex_sync().unwrap();
+
+        assert_eq!(synchronous, threaded);
+    }
+
+    #[test]
+    fn reconstructing_source() {
+        let original = "#!/usr/bin/env sylan\n\
+                        /* A /* nested */ comment. */\n\
+                        fun main() {\n\
+                        \t// Numbers and strings keep their spelling.\n\
+                        \tprintln(0x1F, \"a\\tb\", $\"{n}\")\n\
+                        }\n";
+        let source = Source::from(original.chars().collect::<Vec<char>>());
+        let tokens = Tokens::from(Lexer::from(source))
+            .unwrap()
+            .collect_all()
+            .unwrap();
+
+        assert_eq!(reconstruct_source(&tokens), original);
+    }
 }
@@ -13,6 +13,81 @@ use crate::lexing::tokens::{
     Binding, BranchingAndJumping, DeclarationHead, Macros, Modifier, ModuleDefinitions, Token,
 };
 
+/// Reserved, but not yet used.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "asm",
+    "ast",
+    "alias",
+    "align",
+    "alignto",
+    "arena",
+    "atom",
+    "bind",
+    "blittable",
+    "case",
+    "catch",
+    "co",
+    "constexpr",
+    "comptime",
+    "constructor",
+    "checked",
+    "derives",
+    "diverging",
+    "disasm",
+    "do",
+    "dyn",
+    "dynamic",
+    "embed",
+    "fexpr",
+    "fixed",
+    "fn",
+    "func",
+    "forall",
+    "gc",
+    "get",
+    "infix",
+    "in",
+    "lexemes",
+    "link",
+    "llvm",
+    "macro",
+    "mut",
+    "mutating",
+    "never",
+    "nogc",
+    "noyield",
+    "offset",
+    "offsetof",
+    "pack",
+    "pin",
+    "platform",
+    "prefix",
+    "pragma",
+    "pure",
+    "quasiquote",
+    "raw",
+    "read",
+    "ref",
+    "restrict",
+    "stackalloc",
+    "seq",
+    "struct",
+    "source",
+    "sync",
+    "throws",
+    "tokens",
+    "total",
+    "transient",
+    "try",
+    "unary",
+    "unchecked",
+    "unsafe",
+    "unllvm",
+    "value",
+    "virtual",
+    "where",
+];
+
 pub fn new() -> HashMap<&'static str, Token> {
     let mut map = HashMap::new();
     map.extend(vec![
@@ -35,11 +110,16 @@ pub fn new() -> HashMap<&'static str, Token> {
         // Used
         //
         ("as", Token::Binding(Binding::As)),
+        (
+            "break",
+            Token::BranchingAndJumping(BranchingAndJumping::Break),
+        ),
         ("class", Token::DeclarationHead(DeclarationHead::Class)),
         (
             "else",
             Token::BranchingAndJumping(BranchingAndJumping::Else),
         ),
+        ("enum", Token::DeclarationHead(DeclarationHead::Enum)),
         ("extend", Token::DeclarationHead(DeclarationHead::Extend)),
         ("extends", Token::Extends),
         (
@@ -50,6 +130,7 @@ pub fn new() -> HashMap<&'static str, Token> {
         ("final", Token::Binding(Binding::Final)),
         ("for", Token::BranchingAndJumping(BranchingAndJumping::For)),
         ("fun", Token::DeclarationHead(DeclarationHead::Fun)),
+        ("gen", Token::Gen),
         ("global", Token::Global),
         ("if", Token::BranchingAndJumping(BranchingAndJumping::If)),
         ("ignorable", Token::Modifier(Modifier::Ignorable)),
@@ -66,6 +147,7 @@ pub fn new() -> HashMap<&'static str, Token> {
             Token::DeclarationHead(DeclarationHead::Interface),
         ),
         ("module", Token::DeclarationHead(DeclarationHead::Module)),
+        ("nonvolatile", Token::Modifier(Modifier::Nonvolatile)),
         ("operator", Token::Modifier(Modifier::Operator)),
         ("override", Token::Modifier(Modifier::Override)),
         ("package", Token::DeclarationHead(DeclarationHead::Package)),
@@ -74,7 +156,7 @@ pub fn new() -> HashMap<&'static str, Token> {
             Token::Modifier(Modifier::Accessibility(Accessibility::Public)),
         ),
         ("quote", Token::Macros(Macros::Quote)),
-        ("reader", Token::ReservedKeyword),
+        ("reader", Token::Macros(Macros::Reader)),
         (
             "reject",
             Token::ModuleDefinitions(ModuleDefinitions::Reject),
@@ -97,87 +179,18 @@ pub fn new() -> HashMap<&'static str, Token> {
         ("unquote", Token::Macros(Macros::Unquote)),
         ("use", Token::Use),
         ("var", Token::Binding(Binding::Var)),
+        ("volatile", Token::Modifier(Modifier::Volatile)),
         ("with", Token::With),
+        ("yield", Token::Yield),
         (
             "while",
             Token::BranchingAndJumping(BranchingAndJumping::While),
         ),
-        //
-        // Reserved, but not yet used
-        //
-        ("asm", Token::ReservedKeyword),
-        ("ast", Token::ReservedKeyword),
-        ("alias", Token::ReservedKeyword),
-        ("align", Token::ReservedKeyword),
-        ("alignto", Token::ReservedKeyword),
-        ("arena", Token::ReservedKeyword),
-        ("atom", Token::ReservedKeyword),
-        ("bind", Token::ReservedKeyword),
-        ("blittable", Token::ReservedKeyword),
-        ("case", Token::ReservedKeyword),
-        ("catch", Token::ReservedKeyword),
-        ("co", Token::ReservedKeyword),
-        ("constexpr", Token::ReservedKeyword),
-        ("comptime", Token::ReservedKeyword),
-        ("constructor", Token::ReservedKeyword),
-        ("checked", Token::ReservedKeyword),
-        ("derives", Token::ReservedKeyword),
-        ("diverging", Token::ReservedKeyword),
-        ("disasm", Token::ReservedKeyword),
-        ("do", Token::ReservedKeyword),
-        ("dyn", Token::ReservedKeyword),
-        ("dynamic", Token::ReservedKeyword),
-        ("embed", Token::ReservedKeyword),
-        ("fexpr", Token::ReservedKeyword),
-        ("fixed", Token::ReservedKeyword),
-        ("fn", Token::ReservedKeyword),
-        ("func", Token::ReservedKeyword),
-        ("forall", Token::ReservedKeyword),
-        ("gc", Token::ReservedKeyword),
-        ("gen", Token::ReservedKeyword),
-        ("get", Token::ReservedKeyword),
-        ("infix", Token::ReservedKeyword),
-        ("in", Token::ReservedKeyword),
-        ("lexemes", Token::ReservedKeyword),
-        ("link", Token::ReservedKeyword),
-        ("llvm", Token::ReservedKeyword),
-        ("macro", Token::ReservedKeyword),
-        ("mut", Token::ReservedKeyword),
-        ("mutating", Token::ReservedKeyword),
-        ("never", Token::ReservedKeyword),
-        ("nogc", Token::ReservedKeyword),
-        ("noyield", Token::ReservedKeyword),
-        ("offset", Token::ReservedKeyword),
-        ("offsetof", Token::ReservedKeyword),
-        ("pack", Token::ReservedKeyword),
-        ("pin", Token::ReservedKeyword),
-        ("platform", Token::ReservedKeyword),
-        ("prefix", Token::ReservedKeyword),
-        ("pragma", Token::ReservedKeyword),
-        ("pure", Token::ReservedKeyword),
-        ("quasiquote", Token::ReservedKeyword),
-        ("raw", Token::ReservedKeyword),
-        ("read", Token::ReservedKeyword),
-        ("ref", Token::ReservedKeyword),
-        ("restrict", Token::ReservedKeyword),
-        ("stackalloc", Token::ReservedKeyword),
-        ("seq", Token::ReservedKeyword),
-        ("struct", Token::ReservedKeyword),
-        ("source", Token::ReservedKeyword),
-        ("sync", Token::ReservedKeyword),
-        ("throws", Token::ReservedKeyword),
-        ("tokens", Token::ReservedKeyword),
-        ("total", Token::ReservedKeyword),
-        ("transient", Token::ReservedKeyword),
-        ("try", Token::ReservedKeyword),
-        ("unary", Token::ReservedKeyword),
-        ("unchecked", Token::ReservedKeyword),
-        ("unsafe", Token::ReservedKeyword),
-        ("unllvm", Token::ReservedKeyword),
-        ("yield", Token::ReservedKeyword),
-        ("value", Token::ReservedKeyword),
-        ("virtual", Token::ReservedKeyword),
-        ("where", Token::ReservedKeyword),
     ]);
+    map.extend(
+        RESERVED_KEYWORDS
+            .iter()
+            .map(|&keyword| (keyword, Token::ReservedKeyword(keyword))),
+    );
     map
 }
@@ -1,7 +1,8 @@
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::io;
-use std::sync::mpsc::{channel, Receiver, RecvError, SendError};
+use std::num::ParseIntError;
+use std::sync::mpsc::{channel, Receiver, RecvError};
 use std::thread::{self, JoinHandle};
 
 use crate::common::multiphase::{
@@ -19,28 +20,67 @@ use crate::source::Position;
 
 const LEXER_THREAD_NAME: &str = "Sylan Lexer";
 
-/// A lexed token that remembers its position and "trivia". Trivia is whitespace
-/// on either side. Tracking this allows tooling to pull apart code, refactor
-/// it, and then put it back together without breaking whitespace formatting in
-/// the existing source.
+/// A lexed token that remembers its position and "trivia". Trivia is the
+/// whitespace and comments before it, exactly as written. Tracking this allows
+/// tooling to pull apart code, refactor it, and then put it back together
+/// without breaking whitespace formatting in the existing source.
+///
+/// The token's own text is also kept as written, as the same token can be
+/// spelt in many ways, such as numbers in different bases or strings with
+/// escapes. Together with the trivia, this makes a token stream lossless.
+///
+/// The bracket depth counts the parentheses, square brackets, and braces
+/// enclosing the token. Brackets themselves sit at the depth outside of them.
+/// This lets later phases tell a newline at the top level apart from one
+/// inside brackets, where an expression can carry on over multiple lines.
+///
+/// The token's position is where its text starts, excluding trivia, and its
+/// end is just past the last character of that text.
 #[derive(Clone, Eq, Debug, Default, PartialEq)]
 pub struct LexedToken {
     pub position: Position,
+    pub end: Position,
     pub trivia: Option<String>,
+    pub text: String,
+    pub bracket_depth: usize,
     pub token: Token,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ErrorDescription {
     Described(String),
     Expected(char),
     Unexpected(char),
     PrematureEof,
-    ChannelFailure(String),
-    MalformedNumber(String),
+
+    /// A number literal could not be represented, such as one overflowing an
+    /// `i64`. The span covers the whole literal, including its sign, so the
+    /// bad number can be underlined as-is.
+    MalformedNumber {
+        description: String,
+        start: Position,
+        end: Position,
+    },
+
+    /// The file ended inside a multi-line comment. Nested comments make it
+    /// hard to spot which one is unterminated in a large file, so the nesting
+    /// depth at the end of the file and where the outermost one opened are
+    /// both given.
+    UnterminatedComment {
+        depth: usize,
+        opened_at: Position,
+    },
+
+    /// The file ended inside a string or quoted identifier with a custom
+    /// delimiter. Those can span many lines and contain shorter runs of their
+    /// delimiter, so the opening delimiter and where it was are given.
+    UnterminatedString {
+        delimiter: String,
+        opened_at: Position,
+    },
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Error {
     position: Position,
     description: ErrorDescription,
@@ -58,13 +98,23 @@ type LexedTokenResult = Result<LexedToken, Error>;
 /// The task that lexes and emitted a token stream over a channel. It's a lexed token channel
 /// combined with a join handle on the underlying thread.
 pub struct LexerTask {
-    tokens: Receiver<LexedToken>,
+    tokens: Receiver<LexedTokenResult>,
     lexer_handle: JoinHandle<Result<(), Error>>,
 }
 
 impl LexerTask {
+    /// Wait for the lexer thread to finish. Any tokens not yet received are
+    /// discarded, and the thread stops at its next send rather than lexing the
+    /// rest of the source for nobody, so this returns even if the caller
+    /// stopped reading early.
     pub fn join(self) -> Result<(), LexerTaskError> {
-        let joined = self.lexer_handle.join();
+        let LexerTask {
+            tokens,
+            lexer_handle,
+        } = self;
+        drop(tokens);
+
+        let joined = lexer_handle.join();
         match joined {
             Ok(result) => match result {
                 Ok(()) => Ok(()),
@@ -74,7 +124,7 @@ impl LexerTask {
         }
     }
 
-    pub fn recv(&self) -> Result<LexedToken, RecvError> {
+    pub fn recv(&self) -> Result<LexedTokenResult, RecvError> {
         self.tokens.recv()
     }
 }
@@ -83,6 +133,22 @@ fn is_start_of_literal_with_escapes(c: char) -> bool {
     (c == '\'') || (c == '"') || (c == '$') || (c == '`')
 }
 
+/// Whether the source continues with `word`, and that word is not merely the
+/// start of a longer one.
+fn next_word_is(source: &mut Source, word: &[char]) -> bool {
+    let starts_with_word = source
+        .peek_many(word.len())
+        .filter(|ahead| *ahead == word)
+        .is_some();
+
+    starts_with_word && !source.match_nth(word.len(), |c| c.is_alphanumeric())
+}
+
+/// A reader macro, consulted when its trigger character is next in the source.
+/// It yields `None` to decline, in which case it must not have consumed
+/// anything, letting the lexer carry on as normal.
+pub type Reader = fn(&mut Source) -> Option<Token>;
+
 struct CachedStringPrefixes {
     package_prefix_str: Vec<char>,
     module_prefix_str: Vec<char>,
@@ -100,6 +166,8 @@ struct LexerCache {
 pub struct Lexer {
     source: Source,
     cache: LexerCache,
+    bracket_depth: usize,
+    readers: HashMap<char, Reader>,
 }
 
 impl From<Source> for Lexer {
@@ -115,11 +183,21 @@ impl From<Source> for Lexer {
                     module_prefix_str: ".module".chars().collect(),
                 },
             },
+            bracket_depth: 0,
+            readers: HashMap::new(),
         }
     }
 }
 
 impl Lexer {
+    /// Install a reader macro for the `trigger` character. It is consulted
+    /// before the lexer's own handling of symbols, so it can claim syntax that
+    /// would otherwise lex as operators.
+    pub fn with_reader(mut self, trigger: char, reader: Reader) -> Self {
+        self.readers.insert(trigger, reader);
+        self
+    }
+
     /// Fail at lexing, describing the reason why.
     fn fail<T>(&self, description: impl Into<String>) -> Result<T, Error> {
         Err(Error {
@@ -176,14 +254,21 @@ impl Lexer {
         }
     }
 
-    fn send_error<T>(&self, token: &LexedToken, err: &SendError<T>) -> Error {
-        Error {
-            position: self.source.position,
-            description: ErrorDescription::ChannelFailure(format!(
-                "the token channel failed to send token {:?}: {}",
-                token, err
-            )),
-        }
+    /// Reports an EOF hit within a custom-delimited string or quoted
+    /// identifier as unterminated, recording how it was opened.
+    fn unterminated<T>(
+        &self,
+        lexed: Result<T, Error>,
+        delimiter: String,
+        opened_at: Position,
+    ) -> Result<T, Error> {
+        lexed.map_err(|error| match error.description {
+            ErrorDescription::PrematureEof => self.error(ErrorDescription::UnterminatedString {
+                delimiter,
+                opened_at,
+            }),
+            _ => error,
+        })
     }
 
     // The following methods are sub-lexers that are reentrant and handle the
@@ -193,6 +278,7 @@ impl Lexer {
     // from subsequent characters in the buffer.
 
     fn lex_multi_line_comment(&mut self, buffer: &mut String) -> Option<Error> {
+        let opened_at = self.source.position;
         self.source.discard_many(2);
 
         let mut nesting_level: usize = 1;
@@ -231,7 +317,10 @@ impl Lexer {
         }
 
         if 1 <= nesting_level {
-            Some(self.premature_eof())
+            Some(self.error(ErrorDescription::UnterminatedComment {
+                depth: nesting_level,
+                opened_at,
+            }))
         } else {
             None
         }
@@ -252,6 +341,7 @@ impl Lexer {
     }
 
     fn lex_trivia(&mut self) -> Result<Option<String>, Error> {
+        let start = self.source.position;
         let is_empty = {
             let c = self.source.peek().cloned();
 
@@ -267,7 +357,10 @@ impl Lexer {
         if is_empty {
             Ok(None)
         } else {
-            let mut trivia = String::new();
+            // Comment contents are skipped via the same methods that lex
+            // SyDocs, which need a buffer, but the trivia itself is taken
+            // straight from the source to keep comment delimiters.
+            let mut skipped = String::new();
             loop {
                 let next_char = self.source.peek().cloned();
 
@@ -278,31 +371,32 @@ impl Lexer {
                     && self.source.nth_is(1, '*')
                     && !self.source.nth_is(2, '*')
                 {
-                    if let Some(err) = self.lex_multi_line_comment(&mut trivia) {
+                    if let Some(err) = self.lex_multi_line_comment(&mut skipped) {
                         break Err(err);
                     }
                 } else if (next_char == Some('/')) && self.source.nth_is(1, '/') {
-                    self.lex_single_line_comment(&mut trivia)
+                    self.lex_single_line_comment(&mut skipped)
                 } else if let Some((c, true)) = next_char.map(|x| (x, x.is_whitespace())) {
-                    trivia.push(c);
+                    skipped.push(c);
                     self.source.discard();
                 } else {
-                    break Ok(Some(trivia));
+                    break Ok(Some(self.source.text_since(start)));
                 }
             }
         }
     }
 
     fn lex_version(&mut self) -> TokenResult {
+        let start = self.source.position;
         self.source.discard();
 
-        self.lex_absolute_number()
-            .map(|Number(real, fractional)| {
+        self.lex_absolute_number(start)
+            .map(|Number(real, fractional, _)| {
                 // TODO: lex this properly. Unlike an absolute number, it must support more than one
                 // decimal place.
                 Token::Version(Version {
                     major: real as u64,
-                    minor: fractional,
+                    minor: fractional as u64,
                     patch: 0,
                 })
             })
@@ -311,12 +405,24 @@ impl Lexer {
     }
 
     fn lex_number(&mut self) -> TokenResult {
-        self.lex_absolute_number()
-            .map(|Number(real, fractional)| {
-                Token::Literal(Literal::Number(Number(real, fractional)))
-            })
-            .map(Ok)
-            .unwrap_or_else(|_| self.fail("invalid number"))
+        // Signs are handled only here; absolute numbers, such as those in
+        // versions, never have them.
+        let start = self.source.position;
+        let negative = match self.source.peek() {
+            Some('-') => {
+                self.source.discard();
+                true
+            }
+            Some('+') => {
+                self.source.discard();
+                false
+            }
+            _ => false,
+        };
+
+        self.lex_absolute_number(start)
+            .map(|number| if negative { number.negate() } else { number })
+            .map(|number| Token::Literal(Literal::Number(number.without_trailing_zeros())))
     }
 
     fn lex_rest_of_word(&mut self, buffer: &mut String) {
@@ -337,6 +443,27 @@ impl Lexer {
         multiphase::Identifier::from(word)
     }
 
+    /// Identifiers in interpolations stop at the string's delimiter, which
+    /// would otherwise be a valid identifier character. Without that, a
+    /// closing delimiter inside an unclosed interpolation would be swallowed
+    /// into the identifier rather than reported.
+    fn lex_interpolated_identifier(&mut self, delimiter: char) -> multiphase::Identifier {
+        let mut word = String::new();
+        loop {
+            match self.source.peek() {
+                Some(&c)
+                    if !(c.is_whitespace()
+                        || (c == delimiter)
+                        || self.cache.non_word_chars.contains(&c)) =>
+                {
+                    self.source.discard();
+                    word.push(c);
+                }
+                _ => break multiphase::Identifier::from(word),
+            }
+        }
+    }
+
     fn lex_identifier(&mut self) -> Token {
         Token::Identifier(self.lex_multiphase_identifier())
     }
@@ -403,7 +530,6 @@ impl Lexer {
     ) -> Result<InterpolatedString, Error> {
         let mut string_fragments = vec!["".to_owned()];
         let mut interpolations = Vec::new();
-        let mut start_new_fragment = false;
 
         loop {
             match self.source.peek() {
@@ -423,36 +549,53 @@ impl Lexer {
                             interpolations,
                         });
                     } else {
-                        if start_new_fragment {
-                            string_fragments.push("".to_owned());
-                            start_new_fragment = false;
-                        }
                         string_fragments.last_mut().unwrap().push(c);
                     }
                 }
-                Some(&c) if c == '{' => {
+                Some(&'{') => {
                     let escaped = self.source.nth_is(1, '{');
 
                     if escaped {
-                        self.source.discard();
-                        self.source.discard();
-
-                        if start_new_fragment {
-                            string_fragments.push("".to_owned());
-                            start_new_fragment = false;
-                        }
-                        let last_fragment = string_fragments.last_mut().unwrap();
-                        last_fragment.push('{');
-                        last_fragment.push('{');
+                        self.source.discard_many(2);
+                        string_fragments.last_mut().unwrap().push('{');
                     } else {
                         self.source.discard();
 
-                        let identifier = self.lex_multiphase_identifier();
+                        let slot = self.source.position;
+                        let identifier = self.lex_interpolated_identifier(delimiter);
+                        if identifier.0.is_empty() {
+                            while self.source.match_next(|c| c.is_whitespace()) {
+                                self.source.discard();
+                            }
+                            if self.source.next_is('}') {
+                                break Err(Error {
+                                    description: ErrorDescription::Described(
+                                        "an interpolation needs an identifier between its braces"
+                                            .to_owned(),
+                                    ),
+                                    position: slot,
+                                });
+                            }
+                        }
+                        if self.source.next_is(delimiter) {
+                            break Err(self.error(ErrorDescription::Described(format!(
+                                "an interpolation cannot contain {}; close it with }} first",
+                                delimiter
+                            ))));
+                        }
                         self.expect_and_discard('}')?;
                         interpolations.push(identifier);
-                        start_new_fragment = true;
+                        string_fragments.push("".to_owned());
                     }
                 }
+
+                // A lone closing brace is literal, but is doubled up like an
+                // opening one where it would otherwise look unbalanced.
+                Some(&'}') => {
+                    let escaped = self.source.nth_is(1, '}');
+                    self.source.discard_many(if escaped { 2 } else { 1 });
+                    string_fragments.last_mut().unwrap().push('}');
+                }
                 Some(&c) => {
                     let maybe_escaped = if (c == '\\') && escaping {
                         self.lex_escape_char_in_string_or_char()?
@@ -460,10 +603,6 @@ impl Lexer {
                         self.source.discard();
                         c
                     };
-                    if start_new_fragment {
-                        string_fragments.push("".to_owned());
-                        start_new_fragment = false;
-                    }
                     string_fragments.last_mut().unwrap().push(maybe_escaped);
                 }
                 None => break Err(self.premature_eof()),
@@ -471,12 +610,32 @@ impl Lexer {
         }
     }
 
+    /// Braces are literal in plain strings and never need escaping; only
+    /// interpolated strings give them meaning.
     fn lex_string(&mut self, escaping: bool) -> TokenResult {
         self.source.discard();
         let string = self.lex_string_content('"', 1, escaping)?;
         Ok(Token::Literal(Literal::String(SylanString::from(string))))
     }
 
+    /// Byte strings are for embedding binary data, such as for FFI. They can
+    /// only contain ASCII characters, each becoming a single byte; anything
+    /// else is rejected rather than silently encoded as UTF-8.
+    fn lex_byte_string(&mut self, escaping: bool) -> TokenResult {
+        self.source.discard();
+        self.source.discard();
+        let string = self.lex_string_content('"', 1, escaping)?;
+
+        if let Some(c) = string.chars().find(|c| !c.is_ascii()) {
+            self.fail(format!(
+                "byte strings can only contain ASCII characters, but found {}",
+                c
+            ))
+        } else {
+            Ok(Token::Literal(Literal::ByteString(string.into_bytes())))
+        }
+    }
+
     fn lex_quoted_identifier(&mut self, escaping: bool) -> TokenResult {
         self.source.discard();
         let string = self.lex_string_content('`', 1, escaping)?;
@@ -491,6 +650,7 @@ impl Lexer {
     }
 
     fn lex_string_with_custom_delimiter(&mut self, escaping: bool) -> TokenResult {
+        let opened_at = self.source.position;
         self.source.discard();
         self.source.discard();
         self.source.discard();
@@ -501,11 +661,14 @@ impl Lexer {
             additional_delimiter_count += 1;
         }
 
-        let string = self.lex_string_content('"', additional_delimiter_count + 3, escaping)?;
+        let delimiter_count = additional_delimiter_count + 3;
+        let string = self.lex_string_content('"', delimiter_count, escaping);
+        let string = self.unterminated(string, "\"".repeat(delimiter_count), opened_at)?;
         Ok(Token::Literal(Literal::String(SylanString::from(string))))
     }
 
     fn lex_quoted_identifier_with_custom_delimiter(&mut self, escaping: bool) -> TokenResult {
+        let opened_at = self.source.position;
         self.source.discard();
         self.source.discard();
         self.source.discard();
@@ -516,11 +679,14 @@ impl Lexer {
             additional_delimiter_count += 1;
         }
 
-        let string = self.lex_string_content('`', additional_delimiter_count + 3, escaping)?;
+        let delimiter_count = additional_delimiter_count + 3;
+        let string = self.lex_string_content('`', delimiter_count, escaping);
+        let string = self.unterminated(string, "`".repeat(delimiter_count), opened_at)?;
         Ok(Token::Identifier(Identifier::from(string)))
     }
 
     fn lex_interpolated_string_with_custom_delimiter(&mut self, escaping: bool) -> TokenResult {
+        let opened_at = self.source.position;
         self.source.discard();
         self.source.discard();
         self.source.discard();
@@ -532,8 +698,10 @@ impl Lexer {
             additional_delimiter_count += 1;
         }
 
-        let string =
-            self.lex_interpolated_string_content('"', additional_delimiter_count + 3, escaping)?;
+        let delimiter_count = additional_delimiter_count + 3;
+        let string = self.lex_interpolated_string_content('"', delimiter_count, escaping);
+        let delimiter = format!("${}", "\"".repeat(delimiter_count));
+        let string = self.unterminated(string, delimiter, opened_at)?;
         Ok(Token::Literal(Literal::InterpolatedString(string)))
     }
 
@@ -542,18 +710,19 @@ impl Lexer {
 
         match self.source.peek() {
             Some(&c) => {
-                let result = if escaping && (c == '\\') {
-                    self.lex_escape_char_in_string_or_char()
-                        .map(|c| Token::Literal(Literal::Char(c)))
+                let c = if escaping && (c == '\\') {
+                    self.lex_escape_char_in_string_or_char()?
                 } else {
                     self.source.discard();
-                    Ok(Token::Literal(Literal::Char(c)))
+                    c
                 };
 
-                // Discard the closing '.
-                self.source.discard();
+                // A char literal holds exactly one character, so anything
+                // other than the closing ' is an error rather than the start
+                // of the next token.
+                self.expect_and_discard('\'')?;
 
-                result
+                Ok(Token::Literal(Literal::Char(c)))
             }
             None => Err(self.premature_eof()),
         }
@@ -662,22 +831,14 @@ impl Lexer {
     fn lex_rest_of_this(&mut self) -> TokenResult {
         let package_prefix_str = &self.cache.string_prefixes.package_prefix_str;
         let module_prefix_str = &self.cache.string_prefixes.module_prefix_str;
-        let package_prefix_lookahead = package_prefix_str.len() + 1;
-
-        let ahead = match self.source.peek_many(package_prefix_lookahead) {
-            Some(ahead) => ahead,
-            None => Err(self.premature_eof())?,
-        };
 
-        let result = if ahead.starts_with(package_prefix_str)
-            && !ahead[package_prefix_str.len()].is_alphanumeric()
-        {
-            self.source.discard_many(package_prefix_lookahead);
+        // `this` can legitimately end the source, so running out of
+        // characters while looking ahead just means it's a plain `this`.
+        let result = if next_word_is(&mut self.source, package_prefix_str) {
+            self.source.discard_many(package_prefix_str.len());
             Token::PseudoIdentifier(PseudoIdentifier::ThisPackage)
-        } else if ahead.starts_with(module_prefix_str)
-            && !ahead[module_prefix_str.len()].is_alphanumeric()
-        {
-            self.source.discard_many(module_prefix_str.len() + 1);
+        } else if next_word_is(&mut self.source, module_prefix_str) {
+            self.source.discard_many(module_prefix_str.len());
             Token::PseudoIdentifier(PseudoIdentifier::ThisModule)
         } else {
             Token::PseudoIdentifier(PseudoIdentifier::This)
@@ -686,9 +847,11 @@ impl Lexer {
         Ok(result)
     }
 
-    fn lex_absolute_number(&mut self) -> Result<Number, Error> {
+    /// Lex a number without a sign. `start` is where the whole literal began,
+    /// which can be before this number if it had a sign or a version prefix.
+    fn lex_absolute_number(&mut self, start: Position) -> Result<Number, Error> {
         match self.source.read() {
-            Some(c) if c.is_digit(10) || (c == '-') || (c == '+') => {
+            Some(c) if c.is_ascii_digit() => {
                 let mut real_to_parse = String::new();
                 real_to_parse.push(c);
                 let mut fractional_to_parse = String::new();
@@ -696,7 +859,12 @@ impl Lexer {
                 let mut decimal_place_consumed = false;
                 loop {
                     match self.source.peek().cloned() {
-                        Some('.') if !decimal_place_consumed => {
+                        // A dot without a digit after it is a member access on
+                        // the number, such as `5.seconds`, rather than a decimal place.
+                        Some('.')
+                            if !decimal_place_consumed
+                                && self.source.match_nth(1, |c| c.is_ascii_digit()) =>
+                        {
                             decimal_place_consumed = true;
                             self.source.discard();
                         }
@@ -711,28 +879,43 @@ impl Lexer {
                         _ => break,
                     }
                 }
+                let fractional_digits = fractional_to_parse.len() as u32;
                 if fractional_to_parse.is_empty() {
                     fractional_to_parse.push('0')
                 }
 
+                // Only digits were collected, so parsing can only fail by
+                // overflowing.
                 real_to_parse
                     .parse()
-                    .map_err(|err| {
-                        self.error(ErrorDescription::MalformedNumber(format!(
-                            "lexed real number component {} failed to parse: {}",
-                            real_to_parse, err
-                        )))
+                    .map_err(|_: ParseIntError| {
+                        let description = format!(
+                            "integer literal {} is too large; the limit is {}",
+                            real_to_parse,
+                            i64::MAX
+                        );
+                        self.error(ErrorDescription::MalformedNumber {
+                            description,
+                            start,
+                            end: self.source.position,
+                        })
                     })
                     .and_then(|real| {
                         fractional_to_parse
                             .parse()
-                            .map_err(|err| {
-                                self.error(ErrorDescription::MalformedNumber(format!(
-                                    "lexed fractional number component {} failed to parse: {}",
-                                    real_to_parse, err
-                                )))
+                            .map_err(|_: ParseIntError| {
+                                let description = format!(
+                                    "the fractional part {} has too many digits; the limit is {}",
+                                    fractional_to_parse,
+                                    i64::MAX
+                                );
+                                self.error(ErrorDescription::MalformedNumber {
+                                    description,
+                                    start,
+                                    end: self.source.position,
+                                })
                             })
-                            .map(|fractional| Number(real, fractional))
+                            .map(|fractional| Number(real, fractional, fractional_digits))
                     })
             }
             _ => Err(self.premature_eof()),
@@ -741,6 +924,12 @@ impl Lexer {
 
     fn lex_symbolic(&mut self) -> TokenResult {
         if let Some(c) = self.source.peek().cloned() {
+            if let Some(reader) = self.readers.get(&c) {
+                if let Some(token) = reader(&mut self.source) {
+                    return Ok(token);
+                }
+            }
+
             match c {
                 // The Infix Operators
                 '.' => Ok(self.lex_with_leading_dot()),
@@ -759,8 +948,8 @@ impl Lexer {
                 // Note that `-` or `+` are either parts of a number literal or
                 // binary operators but are _not_ unary operators. This allows
                 // the lexer to avoid distinguishing unary and binary `-` and `+
-                // `solely by whitespace. For negating a variable, use the
-                // `Number#negate` method instead.
+                // `solely by whitespace. The parser instead treats a `-` where
+                // it expects an operand as negating it.
                 '-' => Ok(self.lex_with_leading_hyphen()),
 
                 '/' => {
@@ -1074,6 +1263,11 @@ impl Lexer {
                             }
                         }
                         '\'' => self.lex_char(true),
+                        'b' if self.source.nth_is(1, '"') => self.lex_byte_string(true),
+                        'r' if self.source.nth_is(1, 'b') && self.source.nth_is(2, '"') => {
+                            self.source.discard();
+                            self.lex_byte_string(false)
+                        }
 
                         _ => {
                             let next = self.source.peek_nth(1).cloned();
@@ -1157,16 +1351,51 @@ impl Lexer {
         }
     }
 
+    /// Attach a token's position and trivia, tracking the bracket depth as
+    /// groupings open and close.
+    fn lexed(&mut self, token: Token, position: Position, trivia: Option<String>) -> LexedToken {
+        let text = self.source.text_since(position);
+
+        match token {
+            Token::Grouping(Grouping::CloseBrace)
+            | Token::Grouping(Grouping::CloseParentheses)
+            | Token::Grouping(Grouping::CloseSquareBracket) => {
+                self.bracket_depth = self.bracket_depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+
+        let bracket_depth = self.bracket_depth;
+
+        match token {
+            Token::Grouping(Grouping::OpenBrace)
+            | Token::Grouping(Grouping::OpenParentheses)
+            | Token::Grouping(Grouping::OpenSquareBracket) => {
+                self.bracket_depth += 1;
+            }
+            _ => {}
+        }
+
+        LexedToken {
+            position,
+            end: self.source.position,
+            trivia,
+            text,
+            bracket_depth,
+            token,
+        }
+    }
+
+    /// Lex the next token on the current thread, without the lexer thread that
+    /// `lex` spawns. Once the source runs out, every further call yields
+    /// `Token::Eof` again at the end of the source, so callers can stop
+    /// whenever suits them.
     pub fn lex_next(&mut self) -> LexedTokenResult {
         match self.lex_trivia() {
             Ok(trivia) => {
                 let position = self.source.position;
                 let token = self.lex_non_trivia();
-                token.map(|t| LexedToken {
-                    token: t,
-                    position,
-                    trivia,
-                })
+                token.map(|t| self.lexed(t, position, trivia))
             }
             Err(err) => Err(err),
         }
@@ -1176,16 +1405,13 @@ impl Lexer {
         match self.lex_trivia() {
             Ok(trivia) => {
                 if let Some(&c) = self.source.peek() {
+                    let position = self.source.position;
                     let token = if (c == 'v') && self.source.match_nth(1, |c| c.is_digit(10)) {
                         self.lex_version()
                     } else {
                         self.lex_non_trivia()
                     };
-                    Some(token.map(|t| LexedToken {
-                        token: t,
-                        position: self.source.position,
-                        trivia,
-                    }))
+                    Some(token.map(|t| self.lexed(t, position, trivia)))
                 } else {
                     None
                 }
@@ -1194,14 +1420,17 @@ impl Lexer {
         }
     }
 
+    /// Only the first token of a source, after any shebang, can be a version.
+    fn lex_header_version_or_next(&mut self) -> LexedTokenResult {
+        self.lex_version_or_next_non_trivia()
+            .unwrap_or_else(|| self.lex_next())
+    }
+
     pub fn lex_shebang_at_start_of_source(&mut self) -> Option<LexedTokenResult> {
         if let Some('#') = self.source.peek() {
+            let position = self.source.position;
             match self.lex_shebang() {
-                Ok(shebang) => Some(Ok(LexedToken {
-                    token: shebang.clone(),
-                    position: self.source.position,
-                    trivia: None,
-                })),
+                Ok(shebang) => Some(Ok(self.lexed(shebang, position, None))),
                 Err(err) => Some(Err(err)),
             }
         } else {
@@ -1209,35 +1438,60 @@ impl Lexer {
         }
     }
 
+    /// Lex the whole source on the current thread, returning every token up to
+    /// and including the `Token::Eof`. This suits embedders that cannot or
+    /// would rather not spawn the lexer thread that `lex` uses.
+    pub fn lex_sync(mut self) -> Result<Vec<LexedToken>, Error> {
+        let mut tokens = vec![];
+
+        // Only the very start of the source can have a shebang.
+        if let Some(shebang_result) = self.lex_shebang_at_start_of_source() {
+            tokens.push(shebang_result?);
+        }
+        let mut token = self.lex_header_version_or_next()?;
+
+        loop {
+            let is_eof = token.token == Token::Eof;
+            tokens.push(token);
+            if is_eof {
+                break Ok(tokens);
+            }
+            token = self.lex_next()?;
+        }
+    }
+
     /// Start lexing from the top-level of the source, returning a lexing task running concurrently
     /// in another thread and feeding tokens through a channel as it goes.
     pub fn lex(mut self) -> io::Result<LexerTask> {
         let (tx, rx) = channel();
         let thread = thread::Builder::new().name(LEXER_THREAD_NAME.to_string());
 
-        let handle = thread.spawn(move || loop {
-            if let Some(shebang_result) = self.lex_shebang_at_start_of_source() {
-                let shebang = shebang_result?;
-                tx.send(shebang.clone())
-                    .map_err(|err| self.send_error(&shebang, &err))?;
-            }
+        let handle = thread.spawn(move || {
+            // Only the very start of the source can have a shebang, and a
+            // version header can only come straight after it.
+            let shebang = self.lex_shebang_at_start_of_source();
+            let mut header_pending = shebang.is_some();
+            let mut result = shebang.unwrap_or_else(|| self.lex_header_version_or_next());
 
-            if let Some(version_result) = self.lex_version_or_next_non_trivia() {
-                let version = version_result?;
-                tx.send(version.clone())
-                    .map_err(|err| self.send_error(&version, &err))?;
-            }
+            loop {
+                // Errors are sent on like tokens so that the parser hits them
+                // where they happened, rather than just seeing the stream end.
+                let (finished, error) = match &result {
+                    Ok(lexed) => (lexed.token == Token::Eof, None),
+                    Err(err) => (true, Some(err.clone())),
+                };
 
-            match self.lex_next() {
-                Ok(token) => {
-                    let is_eof = token.token == Token::Eof;
-                    tx.send(token.clone())
-                        .map_err(|err| self.send_error(&token, &err))?;
-                    if is_eof {
-                        break Ok(());
-                    }
+                // A failed send means the receiver is gone, so nobody wants
+                // the rest of the tokens; that is a clean shutdown.
+                if tx.send(result).is_err() || finished {
+                    break error.map_or(Ok(()), Err);
                 }
-                Err(e) => break Err(e),
+                result = if header_pending {
+                    header_pending = false;
+                    self.lex_header_version_or_next()
+                } else {
+                    self.lex_next()
+                };
             }
         });
 
@@ -1318,6 +1572,21 @@ mod tests {
         assert_next(&mut lexer, &Token::Identifier(Identifier::from("abc")));
     }
 
+    #[test]
+    fn bracket_depth() {
+        let mut lexer = test_lexer("a (b [c {\nd}] e) f");
+        let depths = [0, 0, 1, 1, 2, 2, 3, 2, 1, 1, 0, 0, 0];
+
+        for &expected in depths.iter() {
+            let lexed = lexer.lex_next().unwrap();
+            assert_eq!(
+                expected, lexed.bracket_depth,
+                "unexpected depth for {:?}",
+                lexed.token,
+            );
+        }
+    }
+
     #[test]
     fn psuedo_identifiers() {
         let mut lexer =
@@ -1387,15 +1656,133 @@ mod tests {
     #[test]
     fn numbers() {
         let mut lexer = test_lexer("    23  \t  -34   \t\t\n   23   +32 0.32    \t123123123.32");
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(23, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(-34, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(23, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(32, 0))));
-        assert_next(&mut lexer, &Token::Literal(Literal::Number(Number(0, 32))));
         assert_next(
             &mut lexer,
-            &Token::Literal(Literal::Number(Number(123_123_123, 32))),
+            &Token::Literal(Literal::Number(Number(23, 0, 0))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(-34, 0, 0))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(23, 0, 0))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(32, 0, 0))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 32, 2))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(123_123_123, 32, 2))),
+        );
+    }
+
+    #[test]
+    fn signed_numbers() {
+        let mut lexer = test_lexer("-0.5 +0.5 -5 +5 -1.25");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, -5, 1))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 5, 1))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(-5, 0, 0))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(5, 0, 0))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(-1, -25, 2))),
+        );
+    }
+
+    #[test]
+    fn leading_fractional_zeros() {
+        let mut lexer = test_lexer("0.05 0.5 -0.005");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 5, 2))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, 5, 1))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(0, -5, 3))),
+        );
+    }
+
+    #[test]
+    fn trailing_fractional_zeros() {
+        let lex_number = |source| match test_lexer(source).lex_next() {
+            Ok(LexedToken {
+                token: Token::Literal(Literal::Number(number)),
+                ..
+            }) => number,
+            other => panic!("expected a number but got {:?}", other),
+        };
+
+        assert_eq!(lex_number("1.50"), lex_number("1.5"));
+        assert_eq!(lex_number("1.0"), lex_number("1"));
+        assert_eq!(lex_number("-2.000"), Number(-2, 0, 0));
+        assert_eq!(lex_number("0.050"), Number(0, 5, 2));
+
+        // A version's minor part isn't a fraction, so keeps its zeros.
+        assert_eq!(
+            test_lexer("v1.10").lex_sync().unwrap()[0].token,
+            Token::Version(Version {
+                major: 1,
+                minor: 10,
+                patch: 0,
+            }),
+        );
+    }
+
+    #[test]
+    fn number_followed_by_member() {
+        let mut lexer = test_lexer("5.seconds");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(5, 0, 0))),
+        );
+        assert_next(&mut lexer, &Token::Dot);
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("seconds")));
+    }
+
+    #[test]
+    fn byte_strings() {
+        let mut lexer = test_lexer(r#"b"abc" b"\n" rb"\n" bar"#);
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::ByteString(b"abc".to_vec())),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::ByteString(b"\n".to_vec())),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::ByteString(br"\n".to_vec())),
         );
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("bar")));
+    }
+
+    #[test]
+    fn non_ascii_byte_string() {
+        let mut lexer = test_lexer("b\"caf\u{e9}\"");
+        assert!(lexer.lex_next().is_err());
     }
 
     #[test]
@@ -1407,6 +1794,19 @@ mod tests {
         assert_next(&mut lexer, &Token::Literal(Literal::Char('/')));
     }
 
+    #[test]
+    fn multi_character_chars() {
+        let mut lexer = test_lexer("'a' 'ab'");
+        assert_next(&mut lexer, &Token::Literal(Literal::Char('a')));
+        match lexer.lex_next() {
+            Err(Error {
+                description: ErrorDescription::Expected('\''),
+                ..
+            }) => {}
+            other => panic!("expected a missing closing quote but got {:?}", other),
+        }
+    }
+
     #[test]
     fn strings() {
         let mut lexer = test_lexer("  \"abc\\ndef\"   \t \n\n\n\"\"\"\"'123'\"\"\"\"");
@@ -1442,7 +1842,7 @@ mod tests {
         assert_next(
             &mut lexer,
             &Token::Literal(Literal::InterpolatedString(InterpolatedString {
-                string_fragments: vec!["1".to_owned(), "{{23".to_owned()],
+                string_fragments: vec!["1".to_owned(), "{23".to_owned()],
                 interpolations: vec![Identifier::from("x")],
             })),
         );
@@ -1451,7 +1851,7 @@ mod tests {
             &mut lexer,
             &Token::Literal(Literal::InterpolatedString(InterpolatedString {
                 string_fragments: vec![
-                    "ab{{notInterpolated}}c\"\"\t".to_owned(),
+                    "ab{notInterpolated}c\"\"\t".to_owned(),
                     r#"""" "#.to_owned(),
                 ],
                 interpolations: vec![Identifier::from("foobar")],
@@ -1459,6 +1859,120 @@ mod tests {
         );
     }
 
+    #[test]
+    fn unterminated_nested_comment() {
+        let source = "a /* outer /* inner */ /* still open";
+        let mut opened = Source::from(source.chars().collect::<Vec<char>>());
+        opened.discard_many(source.find("/*").unwrap());
+
+        let mut lexer = test_lexer(source);
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("a")));
+        match lexer.lex_next() {
+            Err(Error {
+                description: ErrorDescription::UnterminatedComment { depth, opened_at },
+                ..
+            }) => {
+                assert_eq!(depth, 2);
+                assert_eq!(opened_at, opened.position);
+            }
+            other => panic!("expected an unterminated comment but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unterminated_custom_delimiter_strings() {
+        let source = "a \"\"\"\" still open \"\"\" ";
+        let mut opened = Source::from(source.chars().collect::<Vec<char>>());
+        opened.discard_many(source.find('"').unwrap());
+
+        let mut lexer = test_lexer(source);
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("a")));
+        match lexer.lex_next() {
+            Err(Error {
+                description:
+                    ErrorDescription::UnterminatedString {
+                        delimiter,
+                        opened_at,
+                    },
+                ..
+            }) => {
+                assert_eq!(delimiter, "\"\"\"\"");
+                assert_eq!(opened_at, opened.position);
+            }
+            other => panic!("expected an unterminated string but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn braces_in_plain_and_interpolated_strings() {
+        let mut lexer = test_lexer(r#""{x} {{y}}" $"{x} {{y}}""#);
+
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::String(SylanString::from("{x} {{y}}".to_owned()))),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["".to_owned(), " {y}".to_owned()],
+                interpolations: vec![Identifier::from("x")],
+            })),
+        );
+    }
+
+    #[test]
+    fn adjacent_interpolations() {
+        let mut lexer = test_lexer(r#"$"a{x}{y}b" $"a{x}b{y}""#);
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["a".to_owned(), "".to_owned(), "b".to_owned()],
+                interpolations: vec![Identifier::from("x"), Identifier::from("y")],
+            })),
+        );
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["a".to_owned(), "b".to_owned(), "".to_owned()],
+                interpolations: vec![Identifier::from("x"), Identifier::from("y")],
+            })),
+        );
+    }
+
+    #[test]
+    fn empty_interpolations() {
+        for source in &["$\"{}\"", "$\"a{ }\""] {
+            let mut slot = Source::from(source.chars().collect::<Vec<char>>());
+            slot.discard_many(source.find('{').unwrap() + 1);
+
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::Described(_),
+                    position,
+                }) => assert_eq!(position, slot.position),
+                other => panic!("expected an empty interpolation error but got {:?}", other),
+            }
+        }
+
+        let mut lexer = test_lexer("$\"{{}}\"");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["{}".to_owned()],
+                interpolations: vec![],
+            })),
+        );
+
+        let mut lexer = test_lexer("$\"a}b}}c\"");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec!["a}b}c".to_owned()],
+                interpolations: vec![],
+            })),
+        );
+    }
+
     #[test]
     fn infix_operators() {
         let mut lexer =
@@ -1537,6 +2051,55 @@ mod tests {
         assert_next(&mut lexer, &Token::Identifier(Identifier::from("ab!")));
     }
 
+    #[test]
+    fn question_marks_end_identifiers() {
+        let mut lexer = test_lexer("x?.y f()?");
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("x")));
+        assert_next(&mut lexer, &Token::PostfixOperator(PostfixOperator::Bind));
+        assert_next(&mut lexer, &Token::Dot);
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("y")));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("f")));
+        assert_next(&mut lexer, &Token::Grouping(Grouping::OpenParentheses));
+        assert_next(&mut lexer, &Token::Grouping(Grouping::CloseParentheses));
+        assert_next(&mut lexer, &Token::PostfixOperator(PostfixOperator::Bind));
+    }
+
+    #[test]
+    fn colons_end_identifiers() {
+        let mut lexer = test_lexer("f(label:x) [T:Int]");
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("f")));
+        assert_next(&mut lexer, &Token::Grouping(Grouping::OpenParentheses));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("label")));
+        assert_next(&mut lexer, &Token::Colon);
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("x")));
+        assert_next(&mut lexer, &Token::Grouping(Grouping::CloseParentheses));
+        assert_next(&mut lexer, &Token::Grouping(Grouping::OpenSquareBracket));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("T")));
+        assert_next(&mut lexer, &Token::Colon);
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("Int")));
+    }
+
+    #[test]
+    fn pipes_end_identifiers() {
+        let mut lexer = test_lexer("a|b xs[|i|]");
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("a")));
+        assert_next(
+            &mut lexer,
+            &Token::OverloadableInfixOperator(OverloadableInfixOperator::BitwiseOr),
+        );
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("b")));
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("xs")));
+        assert_next(
+            &mut lexer,
+            &Token::OverloadableSliceOperator(OverloadableSliceOperator::Open),
+        );
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("i")));
+        assert_next(
+            &mut lexer,
+            &Token::OverloadableSliceOperator(OverloadableSliceOperator::Close),
+        );
+    }
+
     #[test]
     fn single_line_comments() {
         let mut lexer = test_lexer("      //    //  abc   ");
@@ -1609,7 +2172,7 @@ mod tests {
         assert!(start_is_shebang(&mut lexer3, &shebang3));
         assert_next(
             &mut lexer3,
-            &Token::Literal(Literal::Number(Number(123, 0))),
+            &Token::Literal(Literal::Number(Number(123, 0, 0))),
         );
 
         let mut failing_lexer = test_lexer("/usr/local/bin/env sylan\n123 321");
@@ -1658,4 +2221,175 @@ mod tests {
             &Token::PseudoIdentifier(PseudoIdentifier::ThisModule),
         );
     }
+
+    #[test]
+    fn versions_only_in_header() {
+        let v2 = Token::Version(Version {
+            major: 2,
+            minor: 0,
+            patch: 0,
+        });
+        for source in &["v2\nv2 + 1", "#!/usr/bin/env sylan\nv2\nv2 + 1"] {
+            let tokens = test_lexer(source)
+                .lex_sync()
+                .unwrap()
+                .into_iter()
+                .map(|lexed| lexed.token)
+                .filter(|token| !matches!(token, Token::Shebang(_)))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                tokens,
+                vec![
+                    v2.clone(),
+                    Token::Identifier(Identifier::from("v2")),
+                    Token::OverloadableInfixOperator(OverloadableInfixOperator::Add),
+                    Token::Literal(Literal::Number(Number(1, 0, 0))),
+                    Token::Eof,
+                ],
+            );
+        }
+    }
+
+    #[test]
+    fn largest_integer_literal() {
+        let mut lexer = test_lexer("9223372036854775807");
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::Number(Number(i64::MAX, 0, 0))),
+        );
+    }
+
+    #[test]
+    fn overflowing_integer_literal() {
+        let mut lexer = test_lexer("9223372036854775808");
+        match lexer.lex_next() {
+            Err(Error {
+                description: ErrorDescription::MalformedNumber { description, .. },
+                ..
+            }) => assert!(description.contains(&i64::MAX.to_string())),
+            other => panic!("expected a malformed number but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn malformed_number_spans() {
+        let assert_span = |source: &str, literal: &str| {
+            let mut start = Source::from(source.chars().collect::<Vec<char>>());
+            start.discard_many(source.find(literal).unwrap());
+            let mut end = Source::from(source.chars().collect::<Vec<char>>());
+            end.discard_many(source.find(literal).unwrap() + literal.len());
+
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description:
+                        ErrorDescription::MalformedNumber {
+                            start: actual_start,
+                            end: actual_end,
+                            ..
+                        },
+                    ..
+                }) => {
+                    assert_eq!(actual_start, start.position);
+                    assert_eq!(actual_end, end.position);
+                }
+                other => panic!("expected a malformed number but got {:?}", other),
+            }
+        };
+
+        assert_span("99999999999999999999999999", "99999999999999999999999999");
+        assert_span(
+            "  -99999999999999999999999999 + 1",
+            "-99999999999999999999999999",
+        );
+    }
+
+    #[test]
+    fn quotes_around_custom_delimiter_interpolations() {
+        let mut lexer = test_lexer(r#"$"""say "{name}" and ""{other}"" {{x}}""""#);
+        assert_next(
+            &mut lexer,
+            &Token::Literal(Literal::InterpolatedString(InterpolatedString {
+                string_fragments: vec![
+                    "say \"".to_owned(),
+                    "\" and \"\"".to_owned(),
+                    "\"\" {x}".to_owned(),
+                ],
+                interpolations: vec![Identifier::from("name"), Identifier::from("other")],
+            })),
+        );
+        assert_next(&mut lexer, &Token::Eof);
+    }
+
+    #[test]
+    fn closing_delimiters_within_interpolations() {
+        for source in &[r#"$"""{name"""}""""#, r#"$"""{name" }""""#, r#"$"{name"}""#] {
+            let mut quote = Source::from(source.chars().collect::<Vec<char>>());
+            quote.discard_many(source.find("name").unwrap() + "name".len());
+
+            match test_lexer(source).lex_next() {
+                Err(Error {
+                    description: ErrorDescription::Described(message),
+                    position,
+                }) => {
+                    assert!(message.contains("close it with }"));
+                    assert_eq!(position, quote.position);
+                }
+                other => panic!("expected an unclosed interpolation but got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn lexing_past_eof() {
+        let mut lexer = test_lexer("a ");
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("a")));
+
+        let first_eof = lexer.lex_next().unwrap();
+        assert_eq!(first_eof.token, Token::Eof);
+        for _ in 0..3 {
+            let eof = lexer.lex_next().unwrap();
+            assert_eq!(eof.token, Token::Eof);
+            assert_eq!(eof.position, first_eof.position);
+        }
+    }
+
+    fn read_double_tilde(source: &mut Source) -> Option<Token> {
+        if source.nth_is(1, '~') {
+            source.discard_many(2);
+            let mut word = String::new();
+            while let Some(&c) = source.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                word.push(c);
+                source.discard();
+            }
+            Some(Token::Custom(word))
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn reader_macros() {
+        let mut lexer = test_lexer("~~hello ~ x").with_reader('~', read_double_tilde);
+
+        assert_next(&mut lexer, &Token::Custom("hello".to_owned()));
+        assert_next(
+            &mut lexer,
+            &Token::OverloadableInfixOperator(OverloadableInfixOperator::Compose),
+        );
+        assert_next(&mut lexer, &Token::Identifier(Identifier::from("x")));
+    }
+
+    #[test]
+    fn dropped_receiver_is_a_clean_shutdown() {
+        let LexerTask {
+            tokens,
+            lexer_handle,
+        } = test_lexer(&"a + ".repeat(100_000)).lex().unwrap();
+        drop(tokens);
+
+        assert!(lexer_handle.join().unwrap().is_ok());
+    }
 }
@@ -6,6 +6,7 @@ use crate::common::version::Version;
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Literal {
+    ByteString(Vec<u8>),
     Char(char),
     InterpolatedString(InterpolatedString),
     String(SylanString),
@@ -14,6 +15,7 @@ pub enum Literal {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum BranchingAndJumping {
+    Break,
     If,
     Else,
     While,
@@ -25,6 +27,7 @@ pub enum BranchingAndJumping {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum DeclarationHead {
     Class,
+    Enum,
     Extend,
 
     /// Extern means quite different things depending on whether it refers to a
@@ -59,6 +62,8 @@ pub enum Modifier {
     Ignorable,
     Operator,
     Override,
+    Volatile,
+    Nonvolatile,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -120,14 +125,23 @@ pub enum Token {
     // Used in both declaration heads and for upper bounds on type parameters.
     Extends,
 
+    // Marks a fun as a generator, which can `yield` values lazily.
+    Gen,
+
+    // Emitted by a reader macro registered on the lexer, holding whatever the
+    // reader consumed.
+    Custom(String),
+
     Rest,
     SubItemSeparator,
     Throw,
     Timeout,
     Use,
+    Yield,
 
-    /// Does nothing but reserve keywords for future use.
-    ReservedKeyword,
+    /// Does nothing but reserve keywords for future use. It keeps the keyword
+    /// so that misusing it can be explained.
+    ReservedKeyword(&'static str),
 
     With,
 }
@@ -44,17 +44,19 @@
 //! require a sort of zipper or lazy tree structure. More research is needed
 //! here. Until then, there is no `ParserTask` equivalent to the `LexerTask`.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::default::Default;
+use std::mem;
 use std::rc::Rc;
 use std::result;
 
 use crate::common::multiphase::{
-    self, Accessibility, Identifier, OverloadableInfixOperator, PseudoIdentifier,
+    self, Accessibility, Identifier, InterpolatedString, OverloadableInfixOperator,
+    PseudoIdentifier, SyDoc,
 };
 use crate::common::peekable_buffer::PeekableBuffer;
 use crate::common::version::Version;
-use crate::lexing::lexer;
+use crate::lexing::lexer::{self, LexedToken, LexerTaskError};
 use crate::lexing::tokens::{
     self, Binding, BranchingAndJumping, DeclarationHead, Grouping, Literal, Macros, Modifier, Token,
 };
@@ -62,21 +64,26 @@ use crate::lexing::Tokens;
 use crate::parsing::{
     modifier_sets::{AccessibilityModifierExtractor, ModifierSets},
     nodes::{
-        Block, Case, CaseMatch, Class, ClassValueParameterFieldUpgrade, CompositePattern, Cond,
-        CondCase, Expression, For, FunModifiers, FunSignature, If, Item, Lambda, LambdaSignature,
-        LambdaValueParameter, MainPackage, Method, Operator, Package, Pattern, PatternGetter,
-        PatternItem, Select, Switch, Symbol, SymbolLookup, Throw, Timeout, TypeArgument,
-        TypeParameter, TypeReference, ValueArgument, ValueParameter,
+        AbstractMethod, ArgumentLabel, Block, Case, CaseMatch, Class,
+        ClassValueParameterFieldUpgrade, CompositePattern, ConcreteMethod, Cond, CondCase,
+        Expression, For, FunModifiers, FunSignature, If, InterpolatedStringPart, Item, Lambda,
+        LambdaSignature, LambdaValueParameter, MainPackage, MemberAccess, Method, MethodModifiers,
+        Operator, OverloadedOperator, OverrideTarget, Package, Pattern, PatternGetter, PatternItem,
+        PrefixOperator, PrimitiveType, ReturnType, Select, Switch, Symbol, SymbolLookup, Throw,
+        Timeout, TypeArgument, TypeArgumentValue, TypeParameter, TypeReference, ValueArgument,
+        ValueParameter, Volatility,
     },
 };
+use crate::source::Position;
 use nodes::CallArguments;
 
 mod modifier_sets;
 mod nodes;
+mod primitive_types;
 
 // TODO: break cycles in scopes to cleanup memory properly.
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum ParserErrorDescription {
     Described(String),
     Expected(Token),
@@ -85,9 +92,12 @@ pub enum ParserErrorDescription {
     PrematureEof,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ParserError {
     description: ParserErrorDescription,
+
+    /// Where parsing failed, for errors that have tracked it.
+    position: Option<Position>,
 }
 
 #[derive(Debug)]
@@ -98,6 +108,12 @@ pub enum Error {
 
 type Result<T> = result::Result<T, Error>;
 
+/// How deeply expressions, patterns, and blocks can nest before parsing gives
+/// up, which keeps pathological input from overflowing the stack. Unoptimised
+/// builds use several kilobytes of stack per level, so this stays well within
+/// the 2 MiB that spawned threads get by default.
+const DEFAULT_MAX_NESTING_DEPTH: usize = 128;
+
 fn new_void() -> TypeReference {
     TypeReference::new(Symbol::Absolute(SymbolLookup(vec![
         Identifier::from("sylan"),
@@ -106,11 +122,157 @@ fn new_void() -> TypeReference {
     ])))
 }
 
+fn is_lambda_arrow(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::LambdaArrow | Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade)
+    )
+}
+
+/// Turn a lexed interpolated string into its parts in source order, with
+/// each interpolated identifier becoming a symbol expression.
+fn interleave_interpolations(string: InterpolatedString) -> Vec<InterpolatedStringPart> {
+    let mut fragments = string.string_fragments.into_iter();
+    let mut parts = vec![];
+    let push_fragment = |parts: &mut Vec<_>, fragment: Option<String>| {
+        if let Some(fragment) = fragment.filter(|fragment| !fragment.is_empty()) {
+            parts.push(InterpolatedStringPart::Fragment(fragment));
+        }
+    };
+
+    push_fragment(&mut parts, fragments.next());
+    for interpolation in string.interpolations {
+        let symbol = Symbol::Relative(SymbolLookup(vec![interpolation]));
+        parts.push(InterpolatedStringPart::Interpolation(Box::new(
+            Expression::Symbol(symbol),
+        )));
+        push_fragment(&mut parts, fragments.next());
+    }
+    parts
+}
+
+/// The keyword of a declaration that can only appear in a package rather than
+/// a block, if the token is one.
+fn package_level_declaration(token: &Token) -> Option<&'static str> {
+    match token {
+        Token::DeclarationHead(DeclarationHead::Class) => Some("class"),
+        Token::DeclarationHead(DeclarationHead::Enum) => Some("enum"),
+        Token::DeclarationHead(DeclarationHead::Interface) => Some("interface"),
+        Token::DeclarationHead(DeclarationHead::Package) => Some("package"),
+        _ => None,
+    }
+}
+
+fn starts_item(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::DeclarationHead(DeclarationHead::Class)
+            | Token::DeclarationHead(DeclarationHead::Enum)
+            | Token::DeclarationHead(DeclarationHead::Extend)
+            | Token::DeclarationHead(DeclarationHead::Interface)
+            | Token::DeclarationHead(DeclarationHead::Package)
+            | Token::DeclarationHead(DeclarationHead::Fun)
+            | Token::Gen
+            | Token::SyDoc(_)
+            | Token::Binding(Binding::Final)
+            | Token::DeclarationHead(DeclarationHead::Extern)
+            | Token::Macros(Macros::Reader)
+            | Token::Macros(Macros::At)
+    )
+}
+
+/// Whether a case matches everything, i.e. has an unguarded `_` pattern.
+fn is_catch_all(case: &Case) -> bool {
+    case.matches.iter().any(|case_match| {
+        (case_match.pattern.item == PatternItem::Ignored) && case_match.guard.is_none()
+    })
+}
+
+/// A parameter passed without an explicit label is labelled by its name,
+/// unless its pattern is more than a plain identifier.
+fn override_target(signature: &FunSignature) -> OverrideTarget {
+    let labels = signature
+        .value_parameters
+        .iter()
+        .map(
+            |parameter| match (&parameter.label, &parameter.pattern.item) {
+                (Some(label), _) => Some(label.clone()),
+                (None, PatternItem::Identifier(name)) => Some(name.clone()),
+                (None, _) => None,
+            },
+        )
+        .collect();
+
+    OverrideTarget {
+        name: signature.name.clone(),
+        labels,
+    }
+}
+
+/// Pipe `argument` into `target` as its first argument. If `target` is already
+/// a call, the argument goes before the call's own.
+fn pipe(argument: Expression, target: Expression) -> Expression {
+    let argument = ValueArgument {
+        label: ArgumentLabel::Positional,
+        value: argument,
+    };
+
+    let call = match target {
+        Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(mut call)) => {
+            call.arguments.arguments.insert(0, argument);
+            nodes::BranchingAndJumping::ExpressionCall(call)
+        }
+        Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(mut call)) => {
+            call.arguments.arguments.insert(0, argument);
+            nodes::BranchingAndJumping::Call(call)
+        }
+        target => nodes::BranchingAndJumping::ExpressionCall(nodes::ExpressionCall {
+            target: Box::new(target),
+            arguments: CallArguments {
+                type_arguments: vec![],
+                arguments: vec![argument],
+            },
+        }),
+    };
+    Expression::BranchingAndJumping(call)
+}
+
 pub struct Parser {
     tokens: Tokens,
     current_scope: Rc<Block>,
     modifier_sets: ModifierSets,
     accessibility_modifier_extractor: AccessibilityModifierExtractor,
+    primitive_types: HashMap<&'static str, PrimitiveType>,
+
+    /// Whether the parser is inside a type's body, the only place where `this`
+    /// refers to something.
+    within_type_body: bool,
+
+    /// Whether the parser is directly inside a generator fun, the only place
+    /// where `yield` can be used.
+    within_generator: bool,
+
+    /// Whether the parser is in an expression that a construct's own block
+    /// directly follows, such as an `if`'s condition, where a `{` starts that
+    /// block rather than a trailing block passed to a call.
+    within_block_header: bool,
+
+    /// The labels of the `for` loops and `with` blocks enclosing the parser's
+    /// position, innermost last, which `break` can target.
+    loop_labels: Vec<Identifier>,
+
+    /// Whether to carry on past items and expressions of the main package that
+    /// fail to parse, recording them as error nodes instead.
+    recovering: bool,
+
+    /// How many expressions, patterns, and blocks enclose the parser's
+    /// position, and how many are allowed before it fails.
+    nesting_depth: usize,
+    max_nesting_depth: usize,
+
+    /// The spans of the items and expressions parsed so far, if the parser was
+    /// asked to record them.
+    spans: Option<Vec<nodes::SpannedNode>>,
 }
 
 impl From<Tokens> for Parser {
@@ -120,6 +282,15 @@ impl From<Tokens> for Parser {
             current_scope: Rc::new(Block::new_root()),
             modifier_sets: Default::default(),
             accessibility_modifier_extractor: AccessibilityModifierExtractor::new(),
+            primitive_types: primitive_types::new(),
+            within_type_body: false,
+            within_generator: false,
+            within_block_header: false,
+            loop_labels: vec![],
+            recovering: false,
+            nesting_depth: 0,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+            spans: None,
         }
     }
 }
@@ -129,19 +300,32 @@ impl Parser {
     // Utilities
     //
 
+    /// Once the lexer fails, the parser just sees the tokens run out, so
+    /// whatever it trips over next is reported as the lexer's error instead.
+    fn error(&self, description: ParserErrorDescription, position: Option<Position>) -> Error {
+        match self.tokens.lexer_error() {
+            Some(err) => Error::Lexer(err.clone()),
+            None => Error::Parser(ParserError {
+                description,
+                position,
+            }),
+        }
+    }
+
     /// Fail at parsing, describing the reason why.
     fn fail<T>(&self, message: impl Into<String>) -> Result<T> {
-        Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::Described(message.into()),
-        }))
+        Err(self.error(ParserErrorDescription::Described(message.into()), None))
+    }
+
+    /// Fail at parsing like `fail`, but also recording where.
+    fn fail_at<T>(&self, position: Option<Position>, message: impl Into<String>) -> Result<T> {
+        Err(self.error(ParserErrorDescription::Described(message.into()), position))
     }
 
     /// Fail at parsing, stating that the `expected` token was expected but
     /// did not appear.
     fn expected<T>(&self, expected: Token) -> Result<T> {
-        Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::Expected(expected),
-        }))
+        Err(self.error(ParserErrorDescription::Expected(expected), None))
     }
 
     /// Return a successful empty result if it is indeed the next token in the
@@ -176,6 +360,8 @@ impl Parser {
         if let Some(lexed) = self.tokens.read() {
             if lexed.token == expected {
                 Ok(())
+            } else if lexed.token == Token::Eof {
+                self.premature_eof()
             } else {
                 self.expected(expected)
             }
@@ -187,16 +373,71 @@ impl Parser {
     /// Fail at parsing, stating that the `unexpected` token was unexpected
     /// and therefore cannot be handled.
     fn unexpected<T>(&self, unexpected: Token) -> Result<T> {
-        Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::Unexpected(unexpected),
-        }))
+        match unexpected {
+            Token::ReservedKeyword(keyword) => self.reserved(keyword),
+            Token::Eof => self.premature_eof(),
+            unexpected => Err(self.error(ParserErrorDescription::Unexpected(unexpected), None)),
+        }
+    }
+
+    /// Fail at parsing because a keyword reserved for future use appeared,
+    /// most likely where an identifier was intended.
+    fn reserved<T>(&self, keyword: &str) -> Result<T> {
+        self.fail(format!(
+            "`{}` is reserved for future use and cannot be used as an identifier",
+            keyword
+        ))
+    }
+
+    /// Run `parse` one level of nesting deeper, failing instead if that goes
+    /// past the maximum nesting depth. The depth is restored afterwards even
+    /// if `parse` fails, as recovering parses carry on after errors.
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.max_nesting_depth <= self.nesting_depth {
+            return self.fail("maximum nesting depth exceeded");
+        }
+        self.nesting_depth += 1;
+        let result = parse(self);
+        self.nesting_depth -= 1;
+        result
+    }
+
+    /// Parse with trailing blocks forbidden if `within` a block header, or
+    /// allowed again if not, such as inside parentheses within one.
+    fn within_block_header<T>(
+        &mut self,
+        within: bool,
+        parse: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let was_within_block_header = mem::replace(&mut self.within_block_header, within);
+        let result = parse(self);
+        self.within_block_header = was_within_block_header;
+        result
+    }
+
+    /// Whether a trailing block follows, which is never the case directly
+    /// within a block header.
+    fn trailing_block_follows(&mut self) -> bool {
+        !self.within_block_header && self.next_is(&Token::Grouping(Grouping::OpenBrace))
+    }
+
+    /// Record that a node was parsed from `start` up to the end of the last
+    /// token read, if spans are being recorded. The node is only built if so,
+    /// as it is a copy.
+    fn record_span(&mut self, start: Option<Position>, node: impl FnOnce() -> nodes::Node) {
+        let end = self.tokens.last_end();
+        if let (Some(spans), Some(start), Some(end)) = (&mut self.spans, start, end) {
+            spans.push(nodes::SpannedNode {
+                span: nodes::Span { start, end },
+                node: node(),
+            });
+        }
     }
 
     /// Fail at parsing because an EOF was encountered unexpectedly.
     fn premature_eof<T>(&self) -> Result<T> {
-        Err(Error::Parser(ParserError {
-            description: ParserErrorDescription::PrematureEof,
-        }))
+        let position = self.tokens.eof_position();
+        Err(self.error(ParserErrorDescription::PrematureEof, position))
     }
 
     //
@@ -216,9 +457,8 @@ impl Parser {
     }
 
     /// Check whether the next token passes the predicate.
-    fn match_next(&mut self, predicate: impl Fn(Token) -> bool) -> bool {
-        self.tokens
-            .match_next(|lexed| predicate(lexed.token.clone()))
+    fn match_next(&mut self, predicate: impl Fn(&Token) -> bool) -> bool {
+        self.tokens.match_next(|lexed| predicate(&lexed.token))
     }
 
     /// Check whether the next token matches `expected`.
@@ -228,9 +468,8 @@ impl Parser {
 
     /// Check whether the `n`th token passes the predicate, where `n` is
     /// zero-indexed.
-    fn match_nth(&mut self, n: usize, predicate: impl Fn(Token) -> bool) -> bool {
-        self.tokens
-            .match_nth(n, |lexed| predicate(lexed.token.clone()))
+    fn match_nth(&mut self, n: usize, predicate: impl Fn(&Token) -> bool) -> bool {
+        self.tokens.match_nth(n, |lexed| predicate(&lexed.token))
     }
 
     /// Check whether the `n`th token matches `expected`, where `n` is
@@ -257,12 +496,15 @@ impl Parser {
                     false
                 }
             });
-            self.tokens.discard();
 
             if is_modifier {
+                let position = self.tokens.peek().map(|lexed| lexed.position);
                 if let Token::Modifier(modifier) = self.read().unwrap() {
                     if results.contains(&modifier) {
-                        self.fail(format!("the modifier {:?} was listed twice", modifier))?;
+                        self.fail_at(
+                            position,
+                            format!("the modifier {:?} was listed twice", modifier),
+                        )?;
                     } else {
                         results.insert(modifier.clone());
                     }
@@ -276,32 +518,59 @@ impl Parser {
     }
 
     fn parse_symbol(&mut self) -> Result<nodes::Symbol> {
-        let mut lookup = vec![];
-
         Ok(
             if let Some(Token::PseudoIdentifier(pseudo_identifier)) = self.peek() {
-                nodes::Symbol::Pseudo(pseudo_identifier)
+                self.tokens.discard();
+
+                // Packages, modules, and `super` are not values, so dotting
+                // into them looks names up rather than accessing members. `this`
+                // and `it` are values, which are left for member accesses.
+                let is_namespace = matches!(
+                    pseudo_identifier,
+                    PseudoIdentifier::ThisPackage
+                        | PseudoIdentifier::ThisModule
+                        | PseudoIdentifier::Super
+                );
+                if is_namespace && self.next_is(&Token::Dot) {
+                    self.tokens.discard();
+                    nodes::Symbol::PseudoLookup(pseudo_identifier, self.parse_symbol_lookup()?)
+                } else {
+                    nodes::Symbol::Pseudo(pseudo_identifier)
+                }
             } else {
                 let new = if self.next_is(&Token::Global) {
                     self.tokens.discard();
                     self.expect_and_discard(Token::Dot)?;
+
+                    // Absolute lookups start from the root of all packages,
+                    // where pseudoidentifiers such as `this` mean nothing.
+                    if let Some(Token::PseudoIdentifier(pseudo_identifier)) = self.peek() {
+                        self.fail(format!(
+                            "`global.` must be followed by a package name rather than {:?}",
+                            pseudo_identifier
+                        ))?;
+                    }
                     nodes::Symbol::Absolute
                 } else {
                     nodes::Symbol::Relative
                 };
-
-                loop {
-                    lookup.push(self.parse_identifier()?);
-                    if self.next_is(&Token::Dot) {
-                        self.tokens.discard();
-                    } else {
-                        break new(SymbolLookup(lookup));
-                    }
-                }
+                new(self.parse_symbol_lookup()?)
             },
         )
     }
 
+    fn parse_symbol_lookup(&mut self) -> Result<SymbolLookup> {
+        let mut lookup = vec![];
+        loop {
+            lookup.push(self.parse_identifier()?);
+            if self.next_is(&Token::Dot) {
+                self.tokens.discard();
+            } else {
+                break Ok(SymbolLookup(lookup));
+            }
+        }
+    }
+
     fn parse_class_definition(&mut self) -> Result<nodes::Type> {
         self.tokens.discard();
 
@@ -357,7 +626,173 @@ impl Parser {
         })
     }
 
+    fn parse_enum_definition(&mut self) -> Result<nodes::Type> {
+        self.tokens.discard();
+
+        let name = self.parse_identifier()?;
+        let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
+            self.tokens.discard();
+            Some(doc)
+        } else {
+            None
+        };
+
+        let has_type_parameters = self.next_is(&Token::Grouping(Grouping::OpenSquareBracket));
+        let type_parameters = if has_type_parameters {
+            self.parse_type_parameter_list()?
+        } else {
+            vec![]
+        };
+
+        let variants = self.parse_enum_variants()?;
+
+        let does_implement = self.next_is(&Token::DeclarationHead(DeclarationHead::Implements));
+        let implements = if does_implement {
+            self.parse_implements_clause()?
+        } else {
+            vec![]
+        };
+
+        let has_body = self.next_is(&Token::Grouping(Grouping::OpenBrace));
+        let (fields, methods, instance_initialiser) = if has_body {
+            self.parse_class_body()?
+        } else {
+            (vec![], vec![], Block::new_root())
+        };
+
+        let class = Class {
+            implements,
+            methods,
+            fields,
+            value_parameters: vec![],
+            instance_initialiser,
+        };
+
+        Ok(nodes::Type {
+            name,
+            type_parameters,
+            item: nodes::TypeItem::Enum(nodes::Enum { variants, class }),
+            sydoc,
+        })
+    }
+
+    fn parse_enum_variants(&mut self) -> Result<Vec<nodes::EnumVariant>> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
+        let mut variants = vec![];
+        let mut seen_discriminants = HashSet::new();
+        // `None` once a variant has taken the largest possible discriminant,
+        // leaving nothing to auto-assign to the next one.
+        let mut next_discriminant = Some(0);
+
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                self.tokens.discard();
+                break Ok(variants);
+            }
+
+            let name = self.parse_identifier()?;
+
+            let parameters = if self.next_is(&Token::Grouping(Grouping::OpenParentheses)) {
+                self.parse_enum_variant_parameters()?
+            } else {
+                vec![]
+            };
+
+            let (discriminant, discriminant_value) = if self.next_is(&Token::Colon) {
+                self.tokens.discard();
+                let value = self.parse_enum_discriminant()?;
+                let literal = nodes::Literal::Number(multiphase::Number(value, 0, 0));
+                (Some(Expression::Literal(literal)), value)
+            } else {
+                match next_discriminant {
+                    Some(value) => (None, value),
+                    None => self.fail(format!(
+                        "the enum variant `{}` cannot be given a discriminant, as the previous variant took the largest one",
+                        name.0
+                    ))?,
+                }
+            };
+            if !seen_discriminants.insert(discriminant_value) {
+                self.fail(format!(
+                    "the enum discriminant {} is used by more than one variant",
+                    discriminant_value
+                ))?;
+            }
+            next_discriminant = discriminant_value.checked_add(1);
+
+            let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
+                self.tokens.discard();
+                Some(doc)
+            } else {
+                None
+            };
+
+            variants.push(nodes::EnumVariant {
+                name,
+                parameters,
+                discriminant,
+                discriminant_value,
+                sydoc,
+            });
+
+            match self.peek() {
+                Some(Token::SubItemSeparator) => {
+                    self.tokens.discard();
+                }
+                Some(Token::Grouping(Grouping::CloseParentheses)) => {}
+                Some(t) => self.unexpected(t)?,
+                None => self.premature_eof()?,
+            }
+        }
+    }
+
+    fn parse_enum_discriminant(&mut self) -> Result<i64> {
+        match self.read() {
+            Some(Token::Literal(Literal::Number(multiphase::Number(value, 0, 0)))) => Ok(value),
+            Some(_) => self.fail("an enum discriminant must be an integer literal"),
+            None => self.premature_eof(),
+        }
+    }
+
+    fn parse_enum_variant_parameters(&mut self) -> Result<Vec<nodes::EnumVariantParameter>> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
+        let mut parameters = vec![];
+
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                self.tokens.discard();
+                break Ok(parameters);
+            }
+
+            let is_labelled = self.match_nth(1, |t| matches!(t, Token::Identifier(..)))
+                && self.match_nth(2, |t| matches!(t, Token::Identifier(..)));
+            let label = if is_labelled {
+                Some(self.parse_identifier()?)
+            } else {
+                None
+            };
+            let name = self.parse_identifier()?;
+            let type_annotation = self.parse_type_reference()?;
+
+            parameters.push(nodes::EnumVariantParameter {
+                label,
+                name,
+                type_annotation,
+            });
+
+            match self.peek() {
+                Some(Token::SubItemSeparator) => {
+                    self.tokens.discard();
+                }
+                Some(Token::Grouping(Grouping::CloseParentheses)) => {}
+                Some(t) => self.unexpected(t)?,
+                None => self.premature_eof()?,
+            }
+        }
+    }
+
     fn parse_class_value_parameters(&mut self) -> Result<Vec<nodes::ClassValueParameter>> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
         let mut parameters = vec![];
 
         loop {
@@ -393,6 +828,47 @@ impl Parser {
         }
     }
 
+    /// Read a balanced group of tokens, brackets included, without parsing
+    /// what they mean. This is how `syntax` parameters take code that only a
+    /// procedural macro knows how to interpret.
+    fn parse_token_tree(&mut self) -> Result<Vec<Token>> {
+        let mut tokens = vec![];
+        let mut closers = vec![];
+        loop {
+            let position = self.tokens.peek().map(|lexed| lexed.position);
+            let token = match self.read() {
+                Some(Token::Eof) | None => self.premature_eof()?,
+                Some(token) => token,
+            };
+            match &token {
+                Token::Grouping(Grouping::OpenBrace) => closers.push(Grouping::CloseBrace),
+                Token::Grouping(Grouping::OpenParentheses) => {
+                    closers.push(Grouping::CloseParentheses)
+                }
+                Token::Grouping(Grouping::OpenSquareBracket) => {
+                    closers.push(Grouping::CloseSquareBracket)
+                }
+                Token::Grouping(closer @ Grouping::CloseBrace)
+                | Token::Grouping(closer @ Grouping::CloseParentheses)
+                | Token::Grouping(closer @ Grouping::CloseSquareBracket) => {
+                    match closers.pop() {
+                        Some(expected) if expected == *closer => {}
+                        _ => self.fail_at(
+                            position,
+                            "a token tree's brackets must be balanced, with each closed by its own kind",
+                        )?,
+                    }
+                }
+                _ => {}
+            }
+            tokens.push(token);
+
+            if closers.is_empty() {
+                break Ok(tokens);
+            }
+        }
+    }
+
     /// Optional labels complicates parsing value parameter lists. Unlike
     /// type parameters, there isn't an `extends` clause to split type
     /// constraints from names and labels.
@@ -407,6 +883,8 @@ impl Parser {
     /// parser would get lost here. Sylan is therefore really committing to this
     /// design decision...
     fn parse_value_parameter(&mut self) -> Result<nodes::ValueParameter> {
+        let leading_sydoc = self.maybe_parse_sydoc();
+
         let is_syntax = if self.match_next(|t| matches!(t, Token::Macros(Macros::Syntax))) {
             self.tokens.discard();
             true
@@ -422,6 +900,32 @@ impl Parser {
             None => self.premature_eof()?,
         };
 
+        let token_tree_follows = self.match_nth(1, |t| {
+            matches!(
+                t,
+                Token::Grouping(Grouping::OpenBrace)
+                    | Token::Grouping(Grouping::OpenParentheses)
+                    | Token::Grouping(Grouping::OpenSquareBracket)
+            )
+        });
+        if is_syntax && token_tree_follows {
+            let pattern = Pattern {
+                item: PatternItem::Identifier(self.parse_identifier()?),
+                bound_match: None,
+            };
+            let token_tree = self.parse_token_tree()?;
+
+            return Ok(ValueParameter {
+                label: None,
+                pattern,
+                type_annotation: None,
+                default_value: None,
+                sydoc: leading_sydoc,
+                is_syntax,
+                token_tree: Some(token_tree),
+            });
+        }
+
         Ok(if let Some(Token::Identifier(..)) = self.peek_nth(1) {
             // Either the type or the start of a parameter pattern after a label.
 
@@ -434,10 +938,11 @@ impl Parser {
                     ValueParameter {
                         label: None,
                         pattern,
-                        type_annotation,
+                        type_annotation: Some(type_annotation),
                         default_value: None,
-                        sydoc: None,
+                        sydoc: leading_sydoc,
                         is_syntax,
+                        token_tree: None,
                     }
                 }
                 Some(Token::SubItemSeparator) => {
@@ -445,14 +950,14 @@ impl Parser {
 
                     let pattern = self.parse_pattern()?;
                     let type_annotation = self.parse_type_reference()?;
-                    self.tokens.discard();
                     ValueParameter {
                         label: None,
                         pattern,
-                        type_annotation,
+                        type_annotation: Some(type_annotation),
                         default_value: None,
-                        sydoc: None,
+                        sydoc: leading_sydoc,
                         is_syntax,
+                        token_tree: None,
                     }
                 }
                 Some(Token::Colon) => {
@@ -462,19 +967,15 @@ impl Parser {
                     let pattern = self.parse_pattern()?;
                     let type_annotation = self.parse_type_reference()?;
                     let default_value = Some(self.parse_default_value()?);
-                    let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
-                        self.tokens.discard();
-                        Some(doc)
-                    } else {
-                        None
-                    };
+                    let sydoc = self.parse_parameter_sydoc(leading_sydoc)?;
                     ValueParameter {
                         label: None,
                         pattern,
-                        type_annotation,
+                        type_annotation: Some(type_annotation),
                         default_value,
                         sydoc,
                         is_syntax,
+                        token_tree: None,
                     }
                 }
                 Some(_) => {
@@ -489,19 +990,15 @@ impl Parser {
                     } else {
                         None
                     };
-                    let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
-                        self.tokens.discard();
-                        Some(doc)
-                    } else {
-                        None
-                    };
+                    let sydoc = self.parse_parameter_sydoc(leading_sydoc)?;
                     ValueParameter {
                         label,
                         pattern,
-                        type_annotation,
+                        type_annotation: Some(type_annotation),
                         default_value,
                         sydoc,
                         is_syntax,
+                        token_tree: None,
                     }
                 }
                 None => self.premature_eof()?,
@@ -516,23 +1013,40 @@ impl Parser {
             } else {
                 None
             };
-            let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
-                self.tokens.discard();
-                Some(doc)
-            } else {
-                None
-            };
+            let sydoc = self.parse_parameter_sydoc(leading_sydoc)?;
             ValueParameter {
                 label: None,
                 pattern,
-                type_annotation,
+                type_annotation: Some(type_annotation),
                 default_value,
                 sydoc,
                 is_syntax,
+                token_tree: None,
             }
         })
     }
 
+    /// Fail if a parameter's label was already used in the same list. Without
+    /// an explicit label, a parameter that is a plain identifier is labelled by
+    /// that identifier.
+    fn check_label_is_unique(
+        &self,
+        seen_labels: &mut HashSet<Identifier>,
+        label: &Option<Identifier>,
+        pattern: &Pattern,
+    ) -> Result<()> {
+        let label = match (label, &pattern.item) {
+            (Some(label), _) | (None, PatternItem::Identifier(label)) => label,
+            _ => return Ok(()),
+        };
+
+        if seen_labels.insert(label.clone()) {
+            Ok(())
+        } else {
+            self.fail(format!("the parameter label `{}` is used twice", label.0))
+        }
+    }
+
     fn parse_default_value(&mut self) -> Result<nodes::Expression> {
         self.expect_and_discard(Token::Colon)?;
         self.parse_expression()
@@ -541,14 +1055,7 @@ impl Parser {
     fn parse_class_parameter_field_upgrade(&mut self) -> Result<nodes::ClassValueParameter> {
         let modifiers = self.parse_modifiers(&self.modifier_sets.field.clone())?;
 
-        let accessibility = self
-            .accessibility_modifier_extractor
-            .extract_accessibility_modifier(&modifiers)
-            .map_err(|err| {
-                Error::Parser(ParserError {
-                    description: ParserErrorDescription::Described(err),
-                })
-            })?;
+        let accessibility = self.accessibility(&modifiers)?;
 
         let field_upgrade = Some(ClassValueParameterFieldUpgrade { accessibility });
 
@@ -577,16 +1084,86 @@ impl Parser {
     fn parse_class_body(
         &mut self,
     ) -> Result<(Vec<nodes::Field>, Vec<nodes::ConcreteMethod>, Block)> {
-        todo!()
+        let was_within_type_body = self.within_type_body;
+        self.within_type_body = true;
+        let body = self.parse_inside_class_body();
+        self.within_type_body = was_within_type_body;
+        body
     }
 
-    fn parse_with(&mut self) -> Result<nodes::Expression> {
-        self.tokens.discard();
-        let scope = self.parse_block()?;
-        Ok(Expression::Context(scope))
-    }
+    fn parse_inside_class_body(
+        &mut self,
+    ) -> Result<(Vec<nodes::Field>, Vec<nodes::ConcreteMethod>, Block)> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
 
-    fn parse_extension(&mut self) -> Result<nodes::Extension> {
+        let mut fields = vec![];
+        let mut methods = vec![];
+        let mut instance_initialiser = Block::within(&self.current_scope);
+
+        loop {
+            match self.peek() {
+                Some(Token::Grouping(Grouping::CloseBrace)) => {
+                    self.tokens.discard();
+                    break Ok((fields, methods, instance_initialiser));
+                }
+                Some(Token::DeclarationHead(DeclarationHead::Fun)) => {
+                    methods.push(self.parse_method(None)?);
+                }
+                Some(Token::Binding(Binding::Var)) => {
+                    fields.push(self.parse_field(None)?);
+                }
+
+                // A SyDoc documents whichever member follows it.
+                Some(Token::SyDoc(_)) => {
+                    let position = self.tokens.peek().map(|lexed| lexed.position);
+                    let sydoc = self.maybe_parse_sydoc();
+                    match self.peek() {
+                        Some(Token::DeclarationHead(DeclarationHead::Fun)) => {
+                            methods.push(self.parse_method(sydoc)?);
+                        }
+                        Some(Token::Binding(Binding::Var)) => {
+                            fields.push(self.parse_field(sydoc)?);
+                        }
+                        Some(_) => self.fail_at(
+                            position,
+                            "a SyDoc in a class body must come before a field or a method",
+                        )?,
+                        None => self.premature_eof()?,
+                    }
+                }
+                Some(_) => {
+                    let expression = self.parse_outermost_expression()?;
+                    instance_initialiser.expressions.push(expression);
+                }
+                None => self.premature_eof()?,
+            }
+        }
+    }
+
+    fn parse_with(&mut self) -> Result<nodes::Expression> {
+        self.tokens.discard();
+
+        let label = if self.match_next(|t| matches!(t, Token::Identifier(..))) {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+
+        if let Some(label) = &label {
+            self.loop_labels.push(label.clone());
+        }
+        let scope = self.parse_block();
+        if label.is_some() {
+            self.loop_labels.pop();
+        }
+
+        Ok(Expression::Context(nodes::Context {
+            label,
+            scope: scope?,
+        }))
+    }
+
+    fn parse_extension(&mut self) -> Result<nodes::Extension> {
         self.tokens.discard();
         unimplemented!();
     }
@@ -594,23 +1171,37 @@ impl Parser {
     fn parse_for(&mut self) -> Result<nodes::For> {
         self.tokens.discard();
 
-        let reiteration_symbol = if self.next_is(&Token::Binding(tokens::Binding::Var)) {
-            self.tokens.discard();
-            None
-        } else if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
-            None
-        } else {
+        let reiteration_symbol = if self.match_next(|t| matches!(t, Token::Identifier(..))) {
             Some(self.parse_identifier()?)
+        } else {
+            None
         };
 
+        if self.next_is(&Token::Binding(tokens::Binding::Var)) {
+            self.tokens.discard();
+        }
+
         let mut bindings = vec![];
         let scope = loop {
             if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
-                break self.parse_block()?;
+                if let Some(label) = &reiteration_symbol {
+                    self.loop_labels.push(label.clone());
+                }
+                let scope = self.parse_block();
+                if reiteration_symbol.is_some() {
+                    self.loop_labels.pop();
+                }
+                break scope?;
             } else {
-                bindings.push(self.parse_local_binding()?);
+                bindings.push(self.within_block_header(true, Self::parse_local_binding)?);
                 if self.next_is(&Token::SubItemSeparator) {
                     self.tokens.discard();
+
+                    // Later bindings can repeat the `var`, although the one at
+                    // the start covers them all.
+                    if self.next_is(&Token::Binding(tokens::Binding::Var)) {
+                        self.tokens.discard();
+                    }
                 }
             }
         };
@@ -625,7 +1216,7 @@ impl Parser {
     fn parse_if(&mut self) -> Result<nodes::If> {
         self.tokens.discard();
 
-        let condition = self.parse_expression()?;
+        let condition = self.within_block_header(true, Self::parse_expression)?;
         let then = self.parse_block()?;
 
         let else_clause = if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::Else)) {
@@ -636,9 +1227,10 @@ impl Parser {
                 if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::If)) {
                     let if_node = self.parse_if()?;
                     Block {
-                        expressions: vec![Expression::BranchingAndJumping(
+                        expressions: vec![],
+                        tail: Some(Box::new(Expression::BranchingAndJumping(
                             nodes::BranchingAndJumping::If(if_node),
-                        )],
+                        ))),
                         bindings: vec![],
                         parent: Some(self.current_scope.clone()),
                     }
@@ -658,23 +1250,39 @@ impl Parser {
     }
 
     fn parse_type_reference(&mut self) -> Result<nodes::TypeReference> {
-        let symbol = self.parse_symbol()?;
-        let type_arguments = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
-            self.parse_type_argument_list()?
-        } else {
-            vec![]
-        };
-        Ok(TypeReference {
-            symbol,
-            type_arguments,
+        self.nested(|parser| {
+            let symbol = parser.parse_symbol()?;
+            let type_arguments = if parser.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
+                parser.parse_type_argument_list()?
+            } else {
+                vec![]
+            };
+            let primitive = parser.primitive_type(&symbol);
+            Ok(TypeReference {
+                symbol,
+                type_arguments,
+                primitive,
+            })
         })
     }
 
+    /// Primitive types are only recognised by their bare names; a qualified
+    /// symbol like `foo.Int8` is always a user-defined type.
+    fn primitive_type(&self, symbol: &Symbol) -> Option<PrimitiveType> {
+        match symbol {
+            Symbol::Relative(SymbolLookup(identifiers)) if identifiers.len() == 1 => {
+                self.primitive_types.get(identifiers[0].0.as_str()).copied()
+            }
+            _ => None,
+        }
+    }
+
     fn parse_composite_pattern_getter(&mut self, next: &Token) -> Result<Option<PatternGetter>> {
         let second_token_is_colon = self.nth_is(1, &Token::Colon);
 
         match &next {
-            Token::Rest => {
+            // Both `..` and `...` omit the rest of the match.
+            Token::Rest | Token::PseudoIdentifier(PseudoIdentifier::Ellipsis) => {
                 self.tokens.discard();
                 self.expect(Token::Grouping(Grouping::CloseParentheses))?;
                 Ok(None)
@@ -682,10 +1290,10 @@ impl Parser {
 
             Token::Identifier(ref identifier) if !second_token_is_colon => {
                 self.tokens.discard();
-                let pattern = Pattern {
+                let pattern = self.parse_getter_binding(Pattern {
                     item: PatternItem::Identifier(identifier.clone()),
                     bound_match: None,
-                };
+                })?;
                 Ok(Some(PatternGetter {
                     name: identifier.clone(),
                     pattern,
@@ -696,17 +1304,28 @@ impl Parser {
                 let name = self.parse_identifier()?;
                 self.expect_and_discard(Token::Colon)?;
                 let pattern = self.parse_pattern()?;
+                let pattern = self.parse_getter_binding(pattern)?;
                 Ok(Some(PatternGetter { name, pattern }))
             }
         }
     }
 
-    fn parse_composite_pattern(&mut self) -> Result<nodes::CompositePattern> {
-        let token = self
-            .peek()
-            .map(Ok)
-            .unwrap_or_else(|| self.premature_eof())?;
+    /// Parse an `as` after a getter's pattern, which binds the whole getter to
+    /// another pattern too, e.g. `Point(x: Some(first) as fx)`.
+    fn parse_getter_binding(&mut self, pattern: Pattern) -> Result<Pattern> {
+        if self.next_is(&Token::Binding(Binding::As)) {
+            self.tokens.discard();
+            let bound = self.parse_pattern()?;
+            Ok(Pattern {
+                bound_match: Some(Box::new(bound)),
+                ..pattern
+            })
+        } else {
+            Ok(pattern)
+        }
+    }
 
+    fn parse_composite_pattern(&mut self) -> Result<nodes::CompositePattern> {
         let infer_enum_type = if self.next_is(&Token::Dot) {
             self.tokens.discard();
             true
@@ -714,6 +1333,11 @@ impl Parser {
             false
         };
 
+        let token = self
+            .peek()
+            .map(Ok)
+            .unwrap_or_else(|| self.premature_eof())?;
+
         if let Token::Identifier(_) = token {
             let r#type = self.parse_type_reference()?;
             self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
@@ -734,6 +1358,9 @@ impl Parser {
                     break true;
                 }
 
+                if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                    break false;
+                }
                 self.expect_and_discard(Token::SubItemSeparator)?;
             };
 
@@ -751,35 +1378,86 @@ impl Parser {
         }
     }
 
-    fn parse_pattern(&mut self) -> Result<nodes::Pattern> {
-        let token = self
-            .tokens
-            .peek()
-            .map(|lexed| Ok(lexed.clone().token))
-            .unwrap_or_else(|| self.premature_eof())?;
+    /// Parse comma-separated patterns in parentheses into a tuple pattern,
+    /// such as `(x, y)`. A single pattern is merely grouped, as with
+    /// expressions.
+    fn parse_tuple_pattern(&mut self) -> Result<nodes::Pattern> {
+        let position = self.tokens.peek().map(|lexed| lexed.position);
+        self.tokens.discard();
+        let pattern = self.parse_pattern()?;
+        if !self.next_is(&Token::SubItemSeparator) {
+            self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
+            return Ok(pattern);
+        }
 
-        let item = self
-            .parse_literal(token.clone())
-            .map(|lexed_token| Ok(PatternItem::Literal(lexed_token)))
-            .unwrap_or_else(|| match token {
-                Token::Identifier(identifier) => Ok(PatternItem::Identifier(identifier)),
-                Token::PseudoIdentifier(PseudoIdentifier::PlaceholderIdentifier) => {
-                    Ok(PatternItem::Ignored)
-                }
-                Token::Rest => {
-                    self.tokens.discard();
-                    let symbol = self.parse_symbol()?;
-                    Ok(PatternItem::BoundSymbol(symbol))
-                }
-                _ => {
-                    let composite = self.parse_composite_pattern()?;
-                    Ok(PatternItem::Composite(composite))
+        let mut elements = vec![pattern];
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                if elements.len() < 2 {
+                    break self.fail_at(
+                        position,
+                        "a tuple pattern needs at least two elements; without the trailing \
+                         comma, a single pattern in parentheses is merely grouped",
+                    );
                 }
-            });
+                self.tokens.discard();
+                break Ok(Pattern {
+                    item: PatternItem::Tuple(elements),
+                    bound_match: None,
+                });
+            }
+            self.expect_and_discard(Token::SubItemSeparator)?;
+            if !self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                elements.push(self.parse_pattern()?);
+            }
+        }
+    }
 
-        Ok(Pattern {
-            item: item?,
-            bound_match: None,
+    fn parse_pattern(&mut self) -> Result<nodes::Pattern> {
+        self.nested(|parser| {
+            let token = parser
+                .tokens
+                .peek()
+                .map(|lexed| Ok(lexed.clone().token))
+                .unwrap_or_else(|| parser.premature_eof())?;
+
+            if token == Token::Grouping(Grouping::OpenParentheses) {
+                return parser.parse_tuple_pattern();
+            }
+
+            let item = parser
+                .parse_literal(token.clone())
+                .map(|lexed_token| {
+                    parser.tokens.discard();
+                    Ok(PatternItem::Literal(lexed_token))
+                })
+                .unwrap_or_else(|| match token {
+                    Token::Identifier(identifier)
+                        if !parser.nth_is(1, &Token::Grouping(Grouping::OpenParentheses)) =>
+                    {
+                        parser.tokens.discard();
+                        Ok(PatternItem::Identifier(identifier))
+                    }
+                    Token::PseudoIdentifier(PseudoIdentifier::PlaceholderIdentifier) => {
+                        parser.tokens.discard();
+                        Ok(PatternItem::Ignored)
+                    }
+                    Token::Rest => {
+                        parser.tokens.discard();
+                        let symbol = parser.parse_symbol()?;
+                        Ok(PatternItem::BoundSymbol(symbol))
+                    }
+                    Token::ReservedKeyword(keyword) => parser.reserved(keyword),
+                    _ => {
+                        let composite = parser.parse_composite_pattern()?;
+                        Ok(PatternItem::Composite(composite))
+                    }
+                });
+
+            Ok(Pattern {
+                item: item?,
+                bound_match: None,
+            })
         })
     }
 
@@ -793,26 +1471,80 @@ impl Parser {
                 break Ok(arguments);
             }
 
+            let explicitly_unlabelled = self.next_is(&Token::PseudoIdentifier(
+                PseudoIdentifier::PlaceholderIdentifier,
+            )) && self.nth_is(1, &Token::Colon);
             let identifier_follows = self.match_next(|t| matches!(t, Token::Identifier(..)));
-            let label = if identifier_follows && self.nth_is(1, &Token::Colon) {
+            let label = if explicitly_unlabelled {
+                self.tokens.discard_many(2);
+                ArgumentLabel::Unlabelled
+            } else if identifier_follows && self.nth_is(1, &Token::Colon) {
                 let identifier = match self.read() {
                     Some(Token::Identifier(identifier)) => identifier,
                     Some(unexpected) => self.unexpected(unexpected)?,
                     None => self.premature_eof()?,
                 };
                 self.tokens.discard();
-                Some(identifier)
+                ArgumentLabel::Labelled(identifier)
             } else {
-                None
+                ArgumentLabel::Positional
             };
 
-            let expression = self.parse_expression()?;
+            let expression = self.within_block_header(false, Self::parse_expression)?;
             let argument = ValueArgument {
                 label,
                 value: expression,
             };
-            arguments.push(argument)
+            arguments.push(argument);
+
+            if self.next_is(&Token::SubItemSeparator) {
+                self.tokens.discard();
+            }
+        }
+    }
+
+    /// Parse a call's value arguments, followed by an optional trailing lambda
+    /// passed as its final argument, e.g. `twice(3) { it * 2 }`. The trailing
+    /// lambda can be labelled to state which parameter it fills, either as a
+    /// block or as a lambda with its arrow:
+    ///
+    /// ```
+    /// fetch(url) completion: {
+    ///     print(it)
+    /// }
+    /// ```
+    ///
+    /// An unlabelled trailing lambda is a trailing block, described by
+    /// `parse_trailing_block`. An arrow after the arguments is instead a
+    /// cascade, as it is after any other operand.
+    fn parse_call_argument_list(&mut self) -> Result<Vec<ValueArgument>> {
+        let mut arguments = self.parse_value_argument_list()?;
+
+        let label_follows = self.match_next(|t| matches!(t, Token::Identifier(..)))
+            && self.nth_is(1, &Token::Colon)
+            && (self.match_nth(2, is_lambda_arrow)
+                || self.nth_is(2, &Token::Grouping(Grouping::OpenBrace)));
+
+        if label_follows {
+            let label = self.parse_identifier()?;
+            self.tokens.discard();
+            let lambda = if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+                self.parse_trailing_block()?
+            } else {
+                self.parse_lambda()?
+            };
+            arguments.push(ValueArgument {
+                label: ArgumentLabel::Labelled(label),
+                value: Expression::Literal(nodes::Literal::Lambda(lambda)),
+            });
+        } else if self.trailing_block_follows() {
+            arguments.push(ValueArgument {
+                label: ArgumentLabel::Positional,
+                value: Expression::Literal(nodes::Literal::Lambda(self.parse_trailing_block()?)),
+            });
         }
+
+        Ok(arguments)
     }
 
     fn parse_type_argument_list(&mut self) -> Result<Vec<TypeArgument>> {
@@ -833,17 +1565,30 @@ impl Parser {
                     None => self.premature_eof()?,
                 };
                 self.tokens.discard();
-                Some(identifier)
+                ArgumentLabel::Labelled(identifier)
             } else {
-                None
+                ArgumentLabel::Positional
             };
 
-            let type_reference = self.parse_type_reference()?;
-            let argument = TypeArgument {
-                label,
-                value: type_reference,
+            let value = if self.next_is(&Token::PseudoIdentifier(
+                PseudoIdentifier::PlaceholderIdentifier,
+            )) {
+                self.tokens.discard();
+                TypeArgumentValue::Wildcard
+            } else {
+                TypeArgumentValue::Given(self.parse_type_reference()?)
             };
-            arguments.push(argument)
+            let argument = TypeArgument { label, value };
+            arguments.push(argument);
+
+            match self.peek() {
+                Some(Token::SubItemSeparator) => {
+                    self.tokens.discard();
+                }
+                Some(Token::Grouping(Grouping::CloseSquareBracket)) => {}
+                Some(t) => self.unexpected(t)?,
+                None => self.premature_eof()?,
+            }
         }
     }
 
@@ -857,6 +1602,16 @@ impl Parser {
     fn parse_inside_import_stems(&mut self) -> Result<Vec<nodes::Import>> {
         let mut imports = vec![];
         loop {
+            if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::If)) {
+                imports.push(self.parse_version_guarded_imports()?);
+                if self.next_is(&Token::SubItemSeparator) {
+                    self.tokens.discard();
+                    continue;
+                } else {
+                    break Ok(imports);
+                }
+            }
+
             let mut whole: Vec<Identifier> = vec![];
             let readers = loop {
                 match self.peek() {
@@ -876,11 +1631,34 @@ impl Parser {
                 self.tokens.discard();
                 let stems = self.parse_inside_import_stems()?;
                 self.expect_and_discard(Token::Grouping(Grouping::CloseBrace))?;
+
+                // Aliasing a whole group would be ambiguous; each leaf must be
+                // aliased individually instead.
+                if self.next_is(&Token::Binding(Binding::As)) {
+                    self.fail("import groups cannot be aliased; alias the items inside instead")?;
+                }
+
                 let stem = nodes::ImportStem::Multiple(stems);
-                (None, stem)
+                let root = if whole.is_empty() {
+                    None
+                } else {
+                    Some(Symbol::Relative(SymbolLookup(whole)))
+                };
+                (root, stem)
             } else {
+                let name = match whole.pop() {
+                    Some(name) => name,
+                    None => self.fail("an import must name at least one item")?,
+                };
+                let alias = if self.next_is(&Token::Binding(Binding::As)) {
+                    self.tokens.discard();
+                    Some(self.parse_identifier()?)
+                } else {
+                    None
+                };
                 let stem = nodes::ImportStem::Single(nodes::ImportSingleStem {
-                    name: whole.pop().unwrap(),
+                    name,
+                    alias,
                     readers,
                 });
                 let root = Symbol::Relative(SymbolLookup(whole));
@@ -898,6 +1676,51 @@ impl Parser {
         }
     }
 
+    /// Versions only lex as such in a file's header, so an import guard's
+    /// version is an identifier such as `v2`, optionally followed by a minor
+    /// version as in `v2.1`.
+    fn parse_version_guarded_imports(&mut self) -> Result<nodes::Import> {
+        self.tokens.discard();
+        let major = match self.read() {
+            Some(Token::Identifier(Identifier(name))) => name
+                .strip_prefix('v')
+                .and_then(|digits| digits.parse().ok()),
+            Some(_) => None,
+            None => self.premature_eof()?,
+        };
+        let major = match major {
+            Some(major) => major,
+            None => self.fail("an import guard must be a version, such as `v2`")?,
+        };
+        let minor = if self.next_is(&Token::Dot) {
+            self.tokens.discard();
+            match self.read() {
+                Some(Token::Literal(Literal::Number(multiphase::Number(minor, 0, 0))))
+                    if minor >= 0 =>
+                {
+                    minor as u64
+                }
+                _ => self.fail("an import guard's minor version must be a whole number")?,
+            }
+        } else {
+            0
+        };
+        let version = Version {
+            major,
+            minor,
+            patch: 0,
+        };
+
+        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+        let imports = self.parse_inside_import_stems()?;
+        self.expect_and_discard(Token::Grouping(Grouping::CloseBrace))?;
+
+        Ok(nodes::Import {
+            root: None,
+            stem: nodes::ImportStem::VersionGuarded(version, imports),
+        })
+    }
+
     fn parse_import_readers_list(&mut self) -> Result<Vec<Symbol>> {
         self.tokens.discard();
         self.expect_and_discard(Token::Macros(Macros::Reader))?;
@@ -923,8 +1746,10 @@ impl Parser {
         unimplemented!()
     }
 
+    /// Parse the upper bounds of a type parameter, starting from its `extends`.
+    /// Multiple bounds are joined with `&`, e.g. `T extends Show & Eq[T]`.
     fn parse_type_constraints(&mut self) -> Result<Vec<TypeReference>> {
-        self.tokens.discard();
+        self.expect_and_discard(Token::Extends)?;
 
         let mut constraints = vec![];
         loop {
@@ -946,6 +1771,7 @@ impl Parser {
             let mut list = vec![];
             self.expect_and_discard(Token::Grouping(Grouping::OpenSquareBracket))?;
             loop {
+                let leading_sydoc = self.maybe_parse_sydoc();
                 let identifier = self.parse_identifier()?;
                 let (label, name) = if self.match_next(|t| matches!(t, Token::Identifier(..))) {
                     (Some(identifier), self.parse_identifier()?)
@@ -959,19 +1785,15 @@ impl Parser {
                     vec![]
                 };
 
+                // Defaults use `:`, the same as for value parameters.
                 let default_value = if self.next_is(&Token::Colon) {
-                    self.expect_and_discard(Token::Binding(Binding::Assign))?;
+                    self.expect_and_discard(Token::Colon)?;
                     Some(self.parse_type_reference()?)
                 } else {
                     None
                 };
 
-                let sydoc = if let Some(Token::SyDoc(doc)) = self.peek() {
-                    self.tokens.discard();
-                    Some(doc)
-                } else {
-                    None
-                };
+                let sydoc = self.parse_parameter_sydoc(leading_sydoc)?;
 
                 list.push(TypeParameter {
                     label,
@@ -994,7 +1816,9 @@ impl Parser {
     }
 
     fn parse_fun_value_parameter_list(&mut self) -> Result<Vec<ValueParameter>> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenParentheses))?;
         let mut parameters = vec![];
+        let mut seen_labels = HashSet::new();
 
         loop {
             if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
@@ -1003,12 +1827,9 @@ impl Parser {
             }
 
             let parameter = self.parse_value_parameter()?;
+            self.check_label_is_unique(&mut seen_labels, &parameter.label, &parameter.pattern)?;
             parameters.push(parameter);
 
-            if self.next_is(&Token::SubItemSeparator) {
-                self.tokens.discard();
-            }
-
             match self.peek() {
                 Some(Token::SubItemSeparator) => {
                     self.tokens.discard();
@@ -1023,15 +1844,28 @@ impl Parser {
         }
     }
 
-    fn parse_lambda_value_parameter_list(&mut self) -> Result<Vec<LambdaValueParameter>> {
+    /// Parse lambda parameters up to the `closing` token, without consuming
+    /// it. Only parenthesised lists are `annotatable` with parameter types.
+    fn parse_lambda_value_parameter_list(
+        &mut self,
+        closing: &Token,
+        annotatable: bool,
+    ) -> Result<Vec<LambdaValueParameter>> {
         let mut parameters = vec![];
+        let mut seen_labels = HashSet::new();
 
         loop {
-            if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+            if self.next_is(closing) {
                 break Ok(parameters);
             }
 
-            let label = if self.match_nth(1, |t| matches!(t, Token::Identifier(..))) {
+            // As with funs, two identifiers before the end of an annotatable
+            // parameter are its name and type rather than a label and name.
+            let ends_parameter =
+                |t: &Token| matches!(t, Token::Colon | Token::SubItemSeparator) || (t == closing);
+            let is_labelled = self.match_nth(1, |t| matches!(t, Token::Identifier(..)))
+                && !(annotatable && self.match_nth(2, ends_parameter));
+            let label = if is_labelled {
                 let label = match self.read() {
                     Some(Token::Identifier(identifier)) => identifier,
                     Some(unexpected) => self.unexpected(unexpected)?,
@@ -1043,6 +1877,14 @@ impl Parser {
             };
 
             let pattern = self.parse_pattern()?;
+            self.check_label_is_unique(&mut seen_labels, &label, &pattern)?;
+
+            let is_annotated = annotatable && !self.match_next(ends_parameter);
+            let explicit_type_annotation = if is_annotated {
+                Some(self.parse_type_reference()?)
+            } else {
+                None
+            };
 
             let default_value = if self.next_is(&Token::Colon) {
                 Some(self.parse_default_value()?)
@@ -1053,6 +1895,7 @@ impl Parser {
             let parameter = nodes::LambdaValueParameter {
                 label,
                 pattern,
+                explicit_type_annotation,
                 default_value,
             };
 
@@ -1064,88 +1907,442 @@ impl Parser {
         }
     }
 
-    fn parse_lambda_result_type_annotation(&mut self) -> Result<Option<TypeReference>> {
+    fn parse_lambda_result_type_annotation(&mut self) -> Result<Option<Box<TypeReference>>> {
         if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
             Ok(None)
         } else {
-            Ok(Some(self.parse_type_reference()?))
+            Ok(Some(Box::new(self.parse_type_reference()?)))
         }
     }
 
+    /// Parameters are either bare, as in `-> x, y { }`, or parenthesised, as
+    /// in `-> (x Int, y) Int { }`. Only the latter can annotate types,
+    /// including the result's. Either can follow type parameters, as in
+    /// `-> [T] (x T) { x }`.
     fn parse_lambda_signature(&mut self) -> Result<LambdaSignature> {
-        let value_parameters = self.parse_lambda_value_parameter_list()?;
+        let type_parameters = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
+            self.parse_type_parameter_list()?
+        } else {
+            vec![]
+        };
+
+        if self.next_is(&Token::Grouping(Grouping::OpenParentheses)) {
+            self.tokens.discard();
+            let closing = Token::Grouping(Grouping::CloseParentheses);
+            let value_parameters = self.parse_lambda_value_parameter_list(&closing, true)?;
+            self.expect_and_discard(closing)?;
+            let explicit_return_type_annotation = self.parse_lambda_result_type_annotation()?;
+
+            Ok(LambdaSignature {
+                type_parameters,
+                value_parameters,
+                explicit_return_type_annotation,
+            })
+        } else {
+            let opening = Token::Grouping(Grouping::OpenBrace);
+            let value_parameters = self.parse_lambda_value_parameter_list(&opening, false)?;
 
-        Ok(LambdaSignature { value_parameters })
+            Ok(LambdaSignature {
+                type_parameters,
+                value_parameters,
+                explicit_return_type_annotation: None,
+            })
+        }
     }
 
     /// Parsing a lambda; this should not happen from a top-level expression, but only a
     /// subexpresion. This is avoid the ambiguity between a lambda literal and the shorthand for
     /// passing a lambda as a final argument, specifically when that shorthand is on a new line.
     fn parse_lambda(&mut self) -> Result<nodes::Lambda> {
-        self.expect_and_discard(Token::LambdaArrow)?;
+        // The arrow is either a lambda arrow or the cascade operator, which
+        // lexes identically but can only mean a lambda in operand position.
+        self.tokens.discard();
         let signature = self.parse_lambda_signature()?;
-        let block = self.parse_block()?;
+        self.parse_lambda_body(signature)
+    }
 
-        Ok(Lambda { signature, block })
+    fn parse_lambda_body(&mut self, signature: LambdaSignature) -> Result<nodes::Lambda> {
+        if signature.value_parameters.is_empty() {
+            self.parse_lambda_scope(signature, Self::parse_parameterless_lambda_body)
+        } else {
+            self.parse_lambda_scope(signature, Self::parse_block)
+        }
     }
 
-    fn parse_fun(&mut self) -> Result<nodes::Fun> {
-        self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Fun))?;
-        let modifiers = self.parse_modifiers(&self.modifier_sets.function.clone())?;
-        let name = self.parse_identifier()?;
+    fn parse_lambda_scope(
+        &mut self,
+        signature: LambdaSignature,
+        parse_block: impl FnOnce(&mut Self) -> Result<nodes::Block>,
+    ) -> Result<nodes::Lambda> {
+        // A lambda is a value in its own right rather than part of the
+        // enclosing body, so it can neither yield on behalf of an enclosing
+        // generator nor break out of enclosing loops.
+        let was_within_generator = self.within_generator;
+        self.within_generator = false;
+        let enclosing_loop_labels = mem::take(&mut self.loop_labels);
+        let block = self.within_block_header(false, parse_block);
+        self.within_generator = was_within_generator;
+        self.loop_labels = enclosing_loop_labels;
+
+        Ok(Lambda {
+            signature,
+            block: block?,
+        })
+    }
 
-        let type_parameters = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
-            self.parse_type_parameter_list()?
-        } else {
-            vec![]
+    /// A block straight after a call's arguments or a callable symbol passes a
+    /// lambda as the call's final argument, as in `xs.map { x -> x * 2 }`. Any
+    /// parameters come before an arrow inside the braces; without them, the
+    /// lambda takes the implicit `it`, as in `xs.map { it * 2 }`.
+    fn parse_trailing_block(&mut self) -> Result<nodes::Lambda> {
+        let arrow = Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade);
+        let mut n = 1;
+        let has_parameters = loop {
+            match self.peek_nth(n) {
+                Some(Token::Identifier(..))
+                | Some(Token::PseudoIdentifier(PseudoIdentifier::PlaceholderIdentifier))
+                | Some(Token::SubItemSeparator) => n += 1,
+                token => break token.as_ref() == Some(&arrow),
+            }
         };
 
-        let value_parameters = self.parse_fun_value_parameter_list()?;
+        if has_parameters {
+            self.tokens.discard();
+            let value_parameters = self.parse_lambda_value_parameter_list(&arrow, false)?;
+            self.tokens.discard();
+            let signature = LambdaSignature {
+                type_parameters: vec![],
+                value_parameters,
+                explicit_return_type_annotation: None,
+            };
+            self.parse_lambda_scope(signature, |parser| parser.parse_inside_block(vec![]))
+        } else {
+            let signature = LambdaSignature {
+                type_parameters: vec![],
+                value_parameters: vec![],
+                explicit_return_type_annotation: None,
+            };
+            self.parse_lambda_scope(signature, Self::parse_parameterless_lambda_body)
+        }
+    }
 
-        // TODO: resolve the parsing ambiguity between:
-        //
-        // * Extern void functions that drop return types, with a symbol on the
-        //   next line in the main package.
-        // * Extern non-void functions that state a return type in the main
-        //   package.
-        let return_type = todo!();
+    /// A lambda without declared parameters can match on its implicit `it`
+    /// parameter instead, with a body made solely of switch cases:
+    ///
+    /// ```
+    /// -> {
+    ///     .Some(x) { x }
+    ///     .None { 0 }
+    /// }
+    /// ```
+    ///
+    /// The body then yields an implicit switch over `it`. Cases and ordinary
+    /// expressions can start alike, so the first one is parsed as an expression
+    /// and reinterpreted as a pattern if a case's block, alternative pattern,
+    /// or guard follows it.
+    fn parse_parameterless_lambda_body(&mut self) -> Result<nodes::Block> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
 
-        let block = self.parse_block()?;
+        let first_pattern = match self.peek() {
+            Some(Token::Dot) => self.parse_pattern()?,
+            Some(Token::Grouping(Grouping::CloseBrace)) | Some(Token::Binding(Binding::Var)) => {
+                return self.parse_inside_block(vec![]);
+            }
+            Some(token) if package_level_declaration(&token).is_some() => {
+                return self.parse_inside_block(vec![]);
+            }
+            _ => {
+                let expression =
+                    self.within_block_header(true, Self::parse_outermost_expression)?;
+
+                let starts_case = self.next_is(&Token::Grouping(Grouping::OpenBrace))
+                    || self.next_is(&Token::SubItemSeparator)
+                    || self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::If));
+                if !starts_case {
+                    return self.parse_inside_block(vec![expression]);
+                }
 
-        let accessibility = self
-            .accessibility_modifier_extractor
-            .extract_accessibility_modifier(&modifiers)
-            .map_err(|msg| {
-                Error::Parser(ParserError {
-                    description: ParserErrorDescription::Described(msg),
-                })
-            })?;
+                self.pattern_from_expression(expression)?
+            }
+        };
 
-        let modifiers = FunModifiers {
-            accessibility,
-            is_extern: todo!(),
-            is_operator: modifiers.contains(&Modifier::Operator),
+        let switch = Switch {
+            expression: Box::new(Expression::Symbol(Symbol::Pseudo(PseudoIdentifier::It))),
+            cases: self.parse_switch_cases(Some(first_pattern))?,
+        };
+
+        let mut block = Block::within(&self.current_scope);
+        block.tail = Some(Box::new(Expression::BranchingAndJumping(
+            nodes::BranchingAndJumping::Switch(switch),
+        )));
+        Ok(block)
+    }
+
+    /// Reinterpret an expression as the pattern written the same way, for
+    /// when only what follows an expression reveals that it was a pattern.
+    fn pattern_from_expression(&self, expression: Expression) -> Result<Pattern> {
+        let item = match expression {
+            Expression::Literal(literal) => PatternItem::Literal(literal),
+            Expression::Symbol(Symbol::Pseudo(PseudoIdentifier::PlaceholderIdentifier)) => {
+                PatternItem::Ignored
+            }
+            Expression::Symbol(Symbol::Relative(SymbolLookup(mut lookup))) if lookup.len() == 1 => {
+                PatternItem::Identifier(lookup.remove(0))
+            }
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call))
+                if call.arguments.type_arguments.is_empty() =>
+            {
+                let mut getters = vec![];
+                for argument in call.arguments.arguments {
+                    let pattern = self.pattern_from_expression(argument.value)?;
+                    let name = match (argument.label, &pattern.item) {
+                        (ArgumentLabel::Labelled(label), _) => label,
+                        (_, PatternItem::Identifier(identifier)) => identifier.clone(),
+                        (_, _) => self.fail(
+                            "only identifier patterns can omit the name of what they match in a \
+                             composite pattern",
+                        )?,
+                    };
+                    getters.push(PatternGetter { name, pattern });
+                }
+
+                PatternItem::Composite(CompositePattern {
+                    r#type: TypeReference::new(call.target),
+                    getters,
+                    infer_enum_type: false,
+                    ignore_rest: false,
+                })
+            }
+            other => self.fail(format!("expected a pattern but got {:?}", other))?,
+        };
+
+        Ok(Pattern {
+            item,
+            bound_match: None,
+        })
+    }
+
+    /// Parse the operator that an operator fun overloads in place of its
+    /// name, returning it as written alongside which operator it is.
+    fn parse_overloaded_operator(&mut self) -> Result<(Identifier, OverloadedOperator)> {
+        let slice_open =
+            Token::OverloadableSliceOperator(multiphase::OverloadableSliceOperator::Open);
+        let slice_close =
+            Token::OverloadableSliceOperator(multiphase::OverloadableSliceOperator::Close);
+
+        match self.tokens.read() {
+            Some(LexedToken {
+                token: Token::OverloadableInfixOperator(operator),
+                text,
+                ..
+            }) => Ok((Identifier::from(text), OverloadedOperator::Infix(operator))),
+
+            Some(lexed) if lexed.token == slice_open => {
+                let mut name = lexed.text;
+                let mut operator = OverloadedOperator::Index;
+                if self.next_is(&Token::Colon) {
+                    self.tokens.discard();
+                    name.push(':');
+                    operator = OverloadedOperator::Slice;
+
+                    if self.next_is(&Token::PseudoIdentifier(PseudoIdentifier::Ellipsis)) {
+                        self.tokens.discard();
+                        name.push_str("...");
+                        operator = OverloadedOperator::MultiSlice;
+                    }
+                }
+                self.expect_and_discard(slice_close)?;
+                name.push_str("|]");
+
+                Ok((Identifier::from(name), operator))
+            }
+
+            Some(lexed) => self.fail_at(
+                Some(lexed.position),
+                "an operator fun must be named by the operator it overloads, such as `+` or `[||]`",
+            ),
+            None => self.premature_eof(),
+        }
+    }
+
+    /// A fun's SyDoc usually follows its name, but it can instead come before
+    /// the whole fun, in which case the caller passes it in as `leading_sydoc`.
+    fn parse_fun_signature(
+        &mut self,
+        modifiers: &HashSet<Modifier>,
+        leading_sydoc: Option<SyDoc>,
+    ) -> Result<FunSignature> {
+        let (name, operator) = if modifiers.contains(&Modifier::Operator) {
+            let (name, operator) = self.parse_overloaded_operator()?;
+            (name, Some(operator))
+        } else {
+            (self.parse_identifier()?, None)
+        };
+
+        let sydoc = match (leading_sydoc, self.maybe_parse_sydoc()) {
+            (Some(_), Some(_)) => {
+                self.fail("a fun can only have one SyDoc, either before the fun or after its name")?
+            }
+            (leading, following) => leading.or(following),
+        };
+
+        let type_parameters = if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
+            self.parse_type_parameter_list()?
+        } else {
+            vec![]
+        };
+
+        let value_parameters = self.parse_fun_value_parameter_list()?;
+
+        // TODO: resolve the parsing ambiguity between:
+        //
+        // * Extern void functions that drop return types, with a symbol on the
+        //   next line in the main package.
+        // * Extern non-void functions that state a return type in the main
+        //   package.
+        //
+        // The `->` token is lexed as the cascade operator, but it can only mean
+        // an introduced return type in this position.
+        let return_type = if self.match_next(|t| {
+            matches!(
+                t,
+                Token::Grouping(Grouping::OpenBrace) | Token::Binding(Binding::Assign)
+            )
+        }) {
+            None
+        } else {
+            if self.next_is(&Token::OverloadableInfixOperator(
+                OverloadableInfixOperator::Cascade,
+            )) {
+                self.tokens.discard();
+            }
+            Some(ReturnType {
+                r#type: self.parse_type_reference()?,
+                ignorable: modifiers.contains(&Modifier::Ignorable),
+            })
         };
 
-        let signature = FunSignature {
+        Ok(FunSignature {
             name,
-            sydoc: None,
+            sydoc,
             type_parameters,
             value_parameters,
             return_type,
-        };
+            operator,
+        })
+    }
+
+    fn accessibility(&self, modifiers: &HashSet<Modifier>) -> Result<Accessibility> {
+        self.accessibility_modifier_extractor
+            .extract_accessibility_modifier(modifiers)
+            .map_err(|msg| {
+                Error::Parser(ParserError {
+                    description: ParserErrorDescription::Described(msg),
+                    position: None,
+                })
+            })
+    }
+
+    fn fun_modifiers(&self, modifiers: &HashSet<Modifier>) -> Result<FunModifiers> {
+        let accessibility = self.accessibility(modifiers)?;
+
+        Ok(FunModifiers {
+            accessibility,
+
+            // TODO: parse the `extern` declaration head.
+            is_extern: false,
+
+            // Only funs can be generators, which they declare with a leading
+            // `gen` rather than a modifier.
+            is_generator: false,
+
+            is_operator: modifiers.contains(&Modifier::Operator),
+        })
+    }
+
+    fn parse_fun(&mut self) -> Result<nodes::Fun> {
+        let leading_sydoc = self.maybe_parse_sydoc();
+
+        let is_generator = self.next_is(&Token::Gen);
+        if is_generator {
+            self.tokens.discard();
+        }
+
+        self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Fun))?;
+        let modifiers = self.parse_modifiers(&self.modifier_sets.function.clone())?;
+        let signature = self.parse_fun_signature(&modifiers, leading_sydoc)?;
+
+        let was_within_generator = self.within_generator;
+        self.within_generator = is_generator;
+        let block = self.parse_fun_body();
+        self.within_generator = was_within_generator;
+
+        let mut fun_modifiers = self.fun_modifiers(&modifiers)?;
+        fun_modifiers.is_generator = is_generator;
 
         Ok(nodes::Fun {
-            modifiers,
+            modifiers: fun_modifiers,
             signature,
-            block,
+            block: block?,
+        })
+    }
+
+    /// A fun's body is either a block or, for one-liners, an `=` followed by
+    /// the single expression it returns, as in `fun double(x Int) Int = x * 2`.
+    fn parse_fun_body(&mut self) -> Result<nodes::Block> {
+        if self.next_is(&Token::Binding(Binding::Assign)) {
+            self.tokens.discard();
+            let expression = self.parse_expression()?;
+
+            Ok(Block {
+                tail: Some(Box::new(expression)),
+                parent: Some(Rc::new(Block::within(&self.current_scope))),
+                ..Block::new_root()
+            })
+        } else {
+            self.parse_block()
+        }
+    }
+
+    fn parse_method(&mut self, leading_sydoc: Option<SyDoc>) -> Result<nodes::ConcreteMethod> {
+        self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Fun))?;
+        let modifiers = self.parse_modifiers(&self.modifier_sets.method.clone())?;
+        let signature = self.parse_fun_signature(&modifiers, leading_sydoc)?;
+        let scope = self.parse_block()?;
+
+        let method_modifiers = MethodModifiers {
+            fun_modifiers: self.fun_modifiers(&modifiers)?,
+            overrides: if modifiers.contains(&Modifier::Override) {
+                Some(override_target(&signature))
+            } else {
+                None
+            },
+        };
+
+        Ok(ConcreteMethod {
+            r#abstract: AbstractMethod {
+                modifiers: method_modifiers,
+                signature,
+            },
+            scope,
         })
     }
 
+    /// A dotted name such as `package a.b.c { }` is shorthand for packages
+    /// nested inside each other, each sharing the declared accessibility. The
+    /// imports and items belong to the innermost package.
     fn parse_package_definition(&mut self) -> Result<nodes::Package> {
         self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Package))?;
+        let modifiers = self.parse_modifiers(&self.modifier_sets.package.clone())?;
+        let accessibility = self.accessibility(&modifiers)?;
+
+        let mut names = vec![self.parse_identifier()?];
+        while self.next_is(&Token::Dot) {
+            self.tokens.discard();
+            names.push(self.parse_identifier()?);
+        }
+        let name = names.pop().unwrap();
 
-        let name = self.parse_identifier()?;
         let has_imports = self.next_is(&Token::Grouping(Grouping::OpenParentheses));
         let imports = if has_imports {
             self.parse_imports()?
@@ -1156,13 +2353,23 @@ impl Parser {
         let items = self.parse_inside_package()?;
         self.expect_and_discard(Token::Grouping(Grouping::CloseBrace))?;
 
-        Ok(nodes::Package {
-            accessibility: Accessibility::Public,
+        let innermost = nodes::Package {
+            accessibility: accessibility.clone(),
             name,
             items,
             sydoc: None,
             imports,
-        })
+        };
+        Ok(names
+            .into_iter()
+            .rev()
+            .fold(innermost, |inner, name| nodes::Package {
+                accessibility: accessibility.clone(),
+                name,
+                items: vec![Item::Package(inner)],
+                sydoc: None,
+                imports: vec![],
+            }))
     }
 
     fn parse_local_var_binding(&mut self) -> Result<nodes::Binding> {
@@ -1173,50 +2380,125 @@ impl Parser {
     fn parse_local_binding(&mut self) -> Result<nodes::Binding> {
         let pattern = self.parse_pattern()?;
 
+        let explicit_type_annotation = if self.next_is(&Token::Binding(Binding::Assign)) {
+            None
+        } else {
+            Some(self.parse_type_reference()?)
+        };
         self.expect_and_discard(Token::Binding(Binding::Assign))?;
 
         let value = self.parse_expression()?;
 
         Ok(nodes::Binding {
             pattern,
-            value: Box::new(value),
-            explicit_type_annotation: None,
+            value: Some(Box::new(value)),
+            explicit_type_annotation,
         })
     }
 
-    fn parse_binding(&mut self) -> Result<nodes::Binding> {
+    fn parse_reader_definition(&mut self) -> Result<nodes::ReaderMacroDefinition> {
         self.tokens.discard();
-        let declaration_modifiers = self.parse_modifiers(&self.modifier_sets.binding.clone())?;
+        let modifiers = self.parse_modifiers(&self.modifier_sets.reader.clone())?;
+        let accessibility = self.accessibility(&modifiers)?;
 
-        let pattern = self.parse_pattern()?;
+        let name = self.parse_identifier()?;
+        self.expect_and_discard(Token::Binding(Binding::Assign))?;
+        let r#macro = self.parse_symbol()?;
+        self.expect_and_discard(Token::BranchingAndJumping(BranchingAndJumping::For))?;
 
-        let explicit_type_annotation = if self.next_is(&Token::Binding(Binding::Assign)) {
-            None
+        let trigger = match self.read() {
+            Some(Token::Literal(Literal::String(trigger))) => trigger,
+            Some(_) => self.fail("a reader's trigger must be a string, such as `\"if\"`")?,
+            None => self.premature_eof()?,
+        };
+
+        Ok(nodes::ReaderMacroDefinition {
+            accessibility,
+            name,
+            r#macro,
+            trigger,
+        })
+    }
+
+    /// An extern final refers to a variable in another compiled artefact. It
+    /// is written either with a leading `extern`, as in
+    /// `extern nonvolatile final x Int`, or with `extern` as its value, as in
+    /// `final x Int = extern`. Either way it must state its type, and is
+    /// volatile unless declared `nonvolatile`.
+    fn parse_binding(&mut self) -> Result<nodes::Final> {
+        let is_extern_prefixed = self.next_is(&Token::DeclarationHead(DeclarationHead::Extern));
+        let mut declaration_modifiers = if is_extern_prefixed {
+            self.tokens.discard();
+            self.parse_modifiers(&self.modifier_sets.extern_binding.clone())?
         } else {
-            Some(self.parse_type_reference()?)
+            HashSet::new()
         };
-        self.expect_and_discard(Token::Binding(Binding::Assign))?;
 
-        let value = self.parse_expression()?;
+        self.expect_and_discard(Token::Binding(Binding::Final))?;
+        for modifier in self.parse_modifiers(&self.modifier_sets.binding.clone())? {
+            if !declaration_modifiers.insert(modifier.clone()) {
+                self.fail(format!("the modifier {:?} was listed twice", modifier))?;
+            }
+        }
+        let accessibility = self.accessibility(&declaration_modifiers)?;
 
-        Ok(nodes::Binding {
-            pattern,
-            value: Box::new(value),
-            explicit_type_annotation,
+        let pattern = self.parse_pattern()?;
+
+        let explicit_type_annotation =
+            if !is_extern_prefixed && self.next_is(&Token::Binding(Binding::Assign)) {
+                None
+            } else {
+                Some(self.parse_type_reference()?)
+            };
+        if !is_extern_prefixed {
+            self.expect_and_discard(Token::Binding(Binding::Assign))?;
+        }
+
+        let volatility = match (
+            declaration_modifiers.contains(&Modifier::Volatile),
+            declaration_modifiers.contains(&Modifier::Nonvolatile),
+        ) {
+            (true, true) => self.fail("an extern final cannot be both volatile and nonvolatile")?,
+            (true, false) => Some(Volatility::Volatile),
+            (false, true) => Some(Volatility::Nonvolatile),
+            (false, false) => None,
+        };
+
+        let (value, volatility) = if is_extern_prefixed {
+            if self.next_is(&Token::Binding(Binding::Assign)) {
+                self.fail(
+                    "an extern final is defined in another artefact, so it cannot have a value",
+                )?;
+            }
+            (None, Some(volatility.unwrap_or(Volatility::Volatile)))
+        } else if self.next_is(&Token::DeclarationHead(DeclarationHead::Extern)) {
+            self.tokens.discard();
+            if explicit_type_annotation.is_none() {
+                self.fail("an extern final must state its type, as in `final x Int = extern`")?;
+            }
+            (None, Some(volatility.unwrap_or(Volatility::Volatile)))
+        } else if volatility.is_some() {
+            self.fail("only extern finals can be volatile or nonvolatile")?
+        } else {
+            (Some(Box::new(self.parse_expression()?)), None)
+        };
+
+        Ok(nodes::Final {
+            accessibility,
+            binding: nodes::Binding {
+                pattern,
+                value,
+                explicit_type_annotation,
+            },
+            sydoc: None,
+            volatility,
         })
     }
 
-    fn parse_field(&mut self) -> Result<nodes::Field> {
+    fn parse_field(&mut self, sydoc: Option<SyDoc>) -> Result<nodes::Field> {
         self.tokens.discard();
         let declaration_modifiers = self.parse_modifiers(&self.modifier_sets.field.clone())?;
-        let accessibility = self
-            .accessibility_modifier_extractor
-            .extract_accessibility_modifier(&declaration_modifiers)
-            .map_err(|msg| {
-                Error::Parser(ParserError {
-                    description: ParserErrorDescription::Described(msg),
-                })
-            })?;
+        let accessibility = self.accessibility(&declaration_modifiers)?;
 
         let pattern = self.parse_pattern()?;
 
@@ -1227,27 +2509,29 @@ impl Parser {
         };
         self.expect_and_discard(Token::Binding(Binding::Assign))?;
 
-        let is_extern = todo!();
-
         let value = self.parse_expression()?;
 
         Ok(nodes::Field {
             accessibility,
-            is_extern,
+
+            // TODO: parse the `extern` declaration head.
+            is_extern: false,
+
             binding: nodes::Binding {
                 pattern,
-                value: Box::new(value),
+                value: Some(Box::new(value)),
                 explicit_type_annotation,
             },
+            sydoc,
         })
     }
 
     fn parse_identifier(&mut self) -> Result<Identifier> {
         if let Some(lexed) = self.tokens.read() {
-            if let Token::Identifier(identifier) = lexed.token {
-                Ok(identifier)
-            } else {
-                self.fail("identifier expected")
+            match lexed.token {
+                Token::Identifier(identifier) => Ok(identifier),
+                Token::ReservedKeyword(keyword) => self.reserved(keyword),
+                _ => self.fail("identifier expected"),
             }
         } else {
             self.premature_eof()
@@ -1265,13 +2549,17 @@ impl Parser {
             let mut matches = vec![];
             if self.next_is(&Token::Timeout) {
                 if timeout.is_none() {
-                    let nanoseconds = Box::new(self.parse_expression()?);
+                    self.tokens.discard();
+                    let nanoseconds =
+                        Box::new(self.within_block_header(true, Self::parse_expression)?);
                     let body = self.parse_block()?;
                     timeout = Some(Timeout { nanoseconds, body });
                 } else {
-                    self.unexpected(Token::Timeout)?;
+                    let position = self.tokens.peek().map(|lexed| lexed.position);
+                    self.fail_at(position, "a `select` can only have one `timeout`")?;
                 }
             } else {
+                self.check_reachable(&cases)?;
                 let body = loop {
                     let pattern = self.parse_pattern()?;
 
@@ -1280,12 +2568,16 @@ impl Parser {
                             self.expect_and_discard(Token::BranchingAndJumping(
                                 BranchingAndJumping::If,
                             ))?;
-                            Some(self.parse_expression()?)
+                            Some(self.within_block_header(true, Self::parse_expression)?)
                         } else {
                             None
                         };
 
-                    matches.push(CaseMatch { pattern, guard });
+                    matches.push(CaseMatch {
+                        pattern,
+                        guard,
+                        narrowed_type: None,
+                    });
 
                     if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
                         break self.parse_block()?;
@@ -1312,9 +2604,15 @@ impl Parser {
 
         let mut cases = vec![];
         loop {
+            if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
+                self.fail("a cond case must have at least one condition before its block")?;
+            }
+
+            // Comma-separated conditions are alternatives that all share the
+            // same block.
             let mut conditions = vec![];
             let then = loop {
-                let expression = self.parse_expression()?;
+                let expression = self.within_block_header(true, Self::parse_expression)?;
                 conditions.push(expression);
 
                 if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
@@ -1333,23 +2631,44 @@ impl Parser {
     }
 
     fn parse_direct_switch(&mut self) -> Result<Switch> {
-        let expression = self.parse_expression()?;
+        let expression = self.within_block_header(true, Self::parse_expression)?;
         self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+        let cases = self.parse_switch_cases(None)?;
+
+        Ok(Switch {
+            expression: Box::new(expression),
+            cases,
+        })
+    }
+
+    /// Parse switch cases up to and including the closing brace. The first
+    /// case's leading pattern might have already been parsed by the caller.
+    fn parse_switch_cases(&mut self, mut first_pattern: Option<Pattern>) -> Result<Vec<Case>> {
         let mut cases = vec![];
 
         loop {
+            // Comma-separated patterns are alternatives that all share the
+            // same body.
             let mut matches = vec![];
             let body = loop {
-                let pattern = self.parse_pattern()?;
+                let pattern = match first_pattern.take() {
+                    Some(pattern) => pattern,
+                    None => self.parse_pattern()?,
+                };
+                let (pattern, narrowed_type) = self.parse_case_binding(pattern)?;
 
                 let guard = if self.next_is(&Token::BranchingAndJumping(BranchingAndJumping::If)) {
                     self.expect_and_discard(Token::BranchingAndJumping(BranchingAndJumping::If))?;
-                    Some(self.parse_expression()?)
+                    Some(self.within_block_header(true, Self::parse_expression)?)
                 } else {
                     None
                 };
 
-                matches.push(CaseMatch { pattern, guard });
+                matches.push(CaseMatch {
+                    pattern,
+                    guard,
+                    narrowed_type,
+                });
 
                 if self.next_is(&Token::Grouping(Grouping::OpenBrace)) {
                     break self.parse_block()?;
@@ -1361,11 +2680,54 @@ impl Parser {
 
             if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
                 self.tokens.discard();
-                break Ok(Switch {
-                    expression: Box::new(expression),
-                    cases,
-                });
+                break Ok(cases);
+            }
+            self.check_reachable(&cases)?;
+        }
+    }
+
+    /// Fail if the last case parsed so far is a catch-all, as another case is
+    /// about to follow it.
+    fn check_reachable(&mut self, cases: &[Case]) -> Result<()> {
+        if matches!(cases.last(), Some(case) if is_catch_all(case)) {
+            let position = self.tokens.peek().map(|lexed| lexed.position);
+            self.fail_at(
+                position,
+                "a `_` case matches everything, so cases after it can never be reached",
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Parse an `as` binding after a case's pattern, if there is one. Binding a
+    /// plain identifier under another name would be pointless, so that
+    /// identifier is instead taken as a type to narrow the matched value to.
+    fn parse_case_binding(&mut self, pattern: Pattern) -> Result<(Pattern, Option<TypeReference>)> {
+        if !self.next_is(&Token::Binding(Binding::As)) {
+            return Ok((pattern, None));
+        }
+        self.tokens.discard();
+        let bound = self.parse_pattern()?;
+
+        match pattern.item {
+            PatternItem::Identifier(type_name) => {
+                let symbol = Symbol::Relative(SymbolLookup(vec![type_name]));
+                let primitive = self.primitive_type(&symbol);
+                let narrowed_type = TypeReference {
+                    symbol,
+                    type_arguments: vec![],
+                    primitive,
+                };
+                Ok((bound, Some(narrowed_type)))
             }
+            item => Ok((
+                Pattern {
+                    item,
+                    bound_match: Some(Box::new(bound)),
+                },
+                None,
+            )),
         }
     }
 
@@ -1388,6 +2750,89 @@ impl Parser {
         }
     }
 
+    /// A label is only taken when followed by arguments, as a bare identifier
+    /// after `continue` could just as well be the next expression.
+    fn parse_continue(&mut self) -> Result<nodes::Continue> {
+        self.tokens.discard();
+
+        let label_follows = self.match_next(|t| matches!(t, Token::Identifier(..)))
+            && self.nth_is(1, &Token::Grouping(Grouping::OpenParentheses));
+        let label = if label_follows {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+
+        let arguments = if self.next_is(&Token::Grouping(Grouping::OpenParentheses)) {
+            self.parse_value_argument_list()?
+        } else {
+            vec![]
+        };
+
+        Ok(nodes::Continue { label, arguments })
+    }
+
+    fn parse_yield(&mut self) -> Result<nodes::Yield> {
+        if !self.within_generator {
+            self.fail("`yield` can only be used inside a `gen fun`")?;
+        }
+
+        self.tokens.discard();
+        let expression = self.parse_expression()?;
+        Ok(nodes::Yield(Box::new(expression)))
+    }
+
+    /// Both the label and the value are optional. An identifier straight
+    /// after `break` is a label if another expression follows it or if it
+    /// names an enclosing loop or `with` block; otherwise it is the value.
+    /// The value is omitted if the block ends straight after the `break` or
+    /// its label.
+    fn parse_break(&mut self) -> Result<nodes::Break> {
+        self.tokens.discard();
+
+        // An identifier is only a label if an enclosing loop or `with` block
+        // has it; otherwise it starts the value, as in `break x + 1`. A second
+        // identifier on the same line can't continue a value, though, so the
+        // first one must have been meant as a label.
+        let label = match self.peek() {
+            Some(Token::Identifier(identifier)) if self.loop_labels.contains(&identifier) => {
+                self.tokens.discard();
+                Some(identifier)
+            }
+            Some(Token::Identifier(identifier)) if self.identifier_follows_on_same_line() => {
+                self.fail(format!(
+                    "`break` targets the label `{}`, but no enclosing loop or `with` block has that label",
+                    identifier.0
+                ))?
+            }
+            _ => None,
+        };
+
+        let value = if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
+            None
+        } else {
+            Some(Box::new(self.parse_expression()?))
+        };
+
+        Ok(nodes::Break { label, value })
+    }
+
+    fn identifier_follows_on_same_line(&mut self) -> bool {
+        match self.tokens.peek_nth(1) {
+            Some(lexed) => {
+                let on_new_line = matches!(
+                    &lexed.trivia,
+                    Some(trivia) if trivia.contains(&['\n', '\r'][..])
+                );
+                matches!(lexed.token, Token::Identifier(..)) && !on_new_line
+            }
+            None => false,
+        }
+    }
+
+    /// A `throw` never yields, so it can stand in for any operand, as in
+    /// `a || throw e`. What is thrown extends as far right as possible:
+    /// `throw e || b` throws `e || b`.
     fn parse_throw(&mut self) -> Result<nodes::Throw> {
         self.tokens.discard();
         let expression = self.parse_expression()?;
@@ -1397,11 +2842,13 @@ impl Parser {
     fn parse_literal(&mut self, token: Token) -> Option<nodes::Literal> {
         match token {
             // Literal tokens are a one-to-one translation to AST nodes
-            // except interpolated strings.
+            // except interpolated strings, whose interpolations become
+            // expressions.
+            Token::Literal(Literal::ByteString(bytes)) => Some(nodes::Literal::ByteString(bytes)),
             Token::Literal(Literal::Char(c)) => Some(nodes::Literal::Char(c)),
-            Token::Literal(Literal::InterpolatedString(string)) => {
-                Some(nodes::Literal::InterpolatedString(string))
-            }
+            Token::Literal(Literal::InterpolatedString(string)) => Some(
+                nodes::Literal::InterpolatedString(interleave_interpolations(string)),
+            ),
             Token::Literal(Literal::Number(number)) => Some(nodes::Literal::Number(number)),
             Token::Literal(Literal::String(string)) => Some(nodes::Literal::String(string)),
             _ => None,
@@ -1410,43 +2857,112 @@ impl Parser {
 
     fn parse_leading_identifier(&mut self) -> Result<nodes::Expression> {
         let symbol = self.parse_symbol()?;
-        if self.next_is(&Token::Grouping(Grouping::OpenSquareBracket)) {
-            let type_arguments = self.parse_type_argument_list()?;
-            let arguments = self.parse_value_argument_list()?;
-            let call = nodes::Call {
-                target: symbol,
-                arguments: CallArguments {
-                    type_arguments,
-                    arguments,
-                },
-            };
-            Ok(Expression::BranchingAndJumping(
-                nodes::BranchingAndJumping::Call(call),
-            ))
+
+        if (symbol == Symbol::Pseudo(PseudoIdentifier::This)) && !self.within_type_body {
+            self.fail("`this` can only be used inside a type body, such as within a method")?;
+        }
+
+        let is_typed_call = self.next_is(&Token::Grouping(Grouping::OpenSquareBracket))
+            && self.square_brackets_precede_call();
+        if is_typed_call {
+            let type_arguments = self.parse_type_argument_list()?;
+            let arguments = self.parse_call_argument_list()?;
+            Ok(self.call_or_record_literal(symbol, type_arguments, arguments))
         } else if self.next_is(&Token::Grouping(Grouping::OpenParentheses)) {
-            let arguments = self.parse_value_argument_list()?;
-            let call = nodes::Call {
+            let arguments = self.parse_call_argument_list()?;
+            Ok(self.call_or_record_literal(symbol, vec![], arguments))
+        } else if self.trailing_block_follows() {
+            // A lambda as the only argument can drop the call's parentheses,
+            // as in `xs.map { x -> x * 2 }`.
+            let lambda = self.parse_trailing_block()?;
+            let arguments = vec![ValueArgument {
+                label: ArgumentLabel::Positional,
+                value: Expression::Literal(nodes::Literal::Lambda(lambda)),
+            }];
+            Ok(self.call_or_record_literal(symbol, vec![], arguments))
+        } else {
+            Ok(nodes::Expression::Symbol(symbol))
+        }
+    }
+
+    fn call_or_record_literal(
+        &self,
+        symbol: Symbol,
+        type_arguments: Vec<TypeArgument>,
+        arguments: Vec<ValueArgument>,
+    ) -> Expression {
+        let names_type = match &symbol {
+            Symbol::Relative(SymbolLookup(identifiers))
+            | Symbol::Absolute(SymbolLookup(identifiers)) => matches!(
+                identifiers.last(),
+                Some(name) if name.0.starts_with(char::is_uppercase)
+            ),
+            _ => false,
+        };
+        let all_labelled = arguments
+            .iter()
+            .all(|argument| matches!(argument.label, ArgumentLabel::Labelled(..)));
+
+        if names_type && !arguments.is_empty() && all_labelled {
+            let primitive = self.primitive_type(&symbol);
+            Expression::RecordLiteral(nodes::RecordLiteral {
+                record_type: Some(TypeReference {
+                    symbol,
+                    type_arguments,
+                    primitive,
+                }),
+                fields: arguments,
+            })
+        } else {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(nodes::Call {
                 target: symbol,
                 arguments: CallArguments {
-                    type_arguments: vec![],
+                    type_arguments,
                     arguments,
                 },
-            };
-            Ok(Expression::BranchingAndJumping(
-                nodes::BranchingAndJumping::Call(call),
-            ))
-        } else {
-            Ok(nodes::Expression::Symbol(self.parse_symbol()?))
+            }))
+        }
+    }
+
+    /// Square brackets after an expression are either type arguments or an
+    /// index, as in `f[T](x)` versus `array[0]`. Only type arguments are
+    /// followed by a call's arguments, so look past the matching `]` for `(`.
+    fn square_brackets_precede_call(&mut self) -> bool {
+        let mut depth: usize = 0;
+        let mut n = 0;
+        loop {
+            match self.peek_nth(n) {
+                Some(Token::Grouping(Grouping::OpenSquareBracket)) => depth += 1,
+                Some(Token::Grouping(Grouping::CloseSquareBracket)) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break self.nth_is(n + 1, &Token::Grouping(Grouping::OpenParentheses));
+                    }
+                }
+                Some(Token::Eof) | None => break false,
+                Some(_) => {}
+            }
+            n += 1;
         }
     }
 
+    fn parse_index(&mut self, expression: nodes::Expression) -> Result<nodes::Expression> {
+        self.expect_and_discard(Token::Grouping(Grouping::OpenSquareBracket))?;
+        let index = self.parse_expression()?;
+        self.expect_and_discard(Token::Grouping(Grouping::CloseSquareBracket))?;
+        Ok(Expression::Operator(Operator::Index(
+            Box::new(expression),
+            Box::new(index),
+        )))
+    }
+
     fn parse_typed_expression_call(
         &mut self,
         expression: nodes::Expression,
     ) -> Result<nodes::ExpressionCall> {
         let type_arguments = self.parse_type_argument_list()?;
 
-        let arguments = self.parse_value_argument_list()?;
+        let arguments = self.parse_call_argument_list()?;
         Ok(nodes::ExpressionCall {
             target: Box::new(expression),
             arguments: CallArguments {
@@ -1460,7 +2976,7 @@ impl Parser {
         &mut self,
         expression: nodes::Expression,
     ) -> Result<nodes::ExpressionCall> {
-        let arguments = self.parse_value_argument_list()?;
+        let arguments = self.parse_call_argument_list()?;
         Ok(nodes::ExpressionCall {
             target: Box::new(expression),
             arguments: CallArguments {
@@ -1486,15 +3002,15 @@ impl Parser {
                 self.tokens.discard();
                 slices.push(nodes::SliceFragment::Ellipsis);
             } else {
-                let mut start = None;
-                let mut step = None;
-                let mut end = None;
-                let mut component_number: usize = 0;
+                let mut components = vec![None];
 
                 loop {
                     if self.next_is(&Token::Colon) {
                         self.tokens.discard();
-                        component_number += 1;
+                        if components.len() == 3 {
+                            self.fail("a slice has at most a start, a step, and an end")?;
+                        }
+                        components.push(None);
                     } else if self.next_is(&Token::SubItemSeparator) {
                         self.tokens.discard();
                         break;
@@ -1503,48 +3019,59 @@ impl Parser {
                     )) {
                         break;
                     } else {
-                        let n = match self.read() {
-                            Some(Token::Literal(Literal::Number(number))) => number,
-                            Some(unexpected) => self.unexpected(unexpected)?,
-                            None => self.premature_eof()?,
-                        };
-                        match component_number {
-                            0 => {
-                                start = Some(n);
+                        let component = components.last_mut().unwrap();
+                        if component.is_some() {
+                            match self.read() {
+                                Some(unexpected) => self.unexpected(unexpected)?,
+                                None => self.premature_eof()?,
                             }
-                            1 => {
-                                step = Some(n);
-                            }
-                            2 => {
-                                end = Some(n);
-                            }
-                            _ => unreachable!(),
-                        }
-
-                        // If only two slice components exist, assume step was
-                        // skipped rather than the end.
-                        if step.is_some() && end.is_none() {
-                            end = step;
-                            step = None;
                         }
+                        *component = Some(self.parse_expression()?);
                     }
                 }
 
-                slices.push(nodes::SliceFragment::Slice(nodes::Slice {
+                // If only two slice components exist, assume step was skipped
+                // rather than the end.
+                let mut components = components.into_iter();
+                let start = components.next().flatten();
+                let (step, end) = match (components.next(), components.next()) {
+                    (Some(end), None) => (None, end),
+                    (step, end) => (step.flatten(), end.flatten()),
+                };
+
+                slices.push(nodes::SliceFragment::Slice(Box::new(nodes::Slice {
                     start,
                     step,
                     end,
-                }))
+                })))
             }
         }
     }
 
     fn parse_expression(&mut self) -> Result<nodes::Expression> {
+        let start = self.tokens.peek().map(|lexed| lexed.position);
+        let expression = self.nested(|parser| {
+            let operand = parser.parse_operand()?;
+            parser.parse_rest_of_expression(operand)
+        });
+        if let Ok(expression) = &expression {
+            self.record_span(start, || nodes::Node::Expression(expression.clone()));
+        }
+        expression
+    }
+
+    /// Parse an expression without anything trailing it.
+    fn parse_operand(&mut self) -> Result<nodes::Expression> {
         let token = self.tokens.peek().cloned();
+        let start = token.as_ref().map(|lexed| lexed.position);
         let expression = match token {
             Some(lexed) => {
                 let token = lexed.token;
-                self.parse_literal(token.clone())
+                let literal = self.parse_literal(token.clone());
+                if literal.is_some() {
+                    self.tokens.discard();
+                }
+                literal
                     .map(|literal| Ok(nodes::Expression::Literal(literal)))
                     .unwrap_or_else(|| match token {
                         // Non-atomic tokens each delegate to a dedicated method.
@@ -1562,11 +3089,18 @@ impl Parser {
                                 )
                             })
                         }
-                        Token::LambdaArrow => self
-                            .parse_lambda()
-                            .map(|f| nodes::Expression::Literal(nodes::Literal::Lambda(f))),
+                        Token::LambdaArrow
+                        | Token::OverloadableInfixOperator(OverloadableInfixOperator::Cascade) => {
+                            self.parse_lambda()
+                                .map(|f| nodes::Expression::Literal(nodes::Literal::Lambda(f)))
+                        }
+                        // Trailing blocks are unambiguous again within
+                        // brackets, even inside a block header.
                         Token::Grouping(Grouping::OpenParentheses) => {
-                            self.parse_grouped_expression()
+                            self.within_block_header(false, Self::parse_grouped_expression)
+                        }
+                        Token::Grouping(Grouping::OpenSquareBracket) => {
+                            self.within_block_header(false, Self::parse_list_literal)
                         }
                         Token::BranchingAndJumping(BranchingAndJumping::Select) => {
                             self.parse_select().map(|select| {
@@ -1575,6 +3109,12 @@ impl Parser {
                                 )
                             })
                         }
+                        Token::PseudoIdentifier(PseudoIdentifier::Continue) => {
+                            self.parse_continue().map(nodes::Expression::Continue)
+                        }
+                        Token::BranchingAndJumping(BranchingAndJumping::Break) => {
+                            self.parse_break().map(nodes::Expression::Break)
+                        }
                         Token::Identifier(..) | Token::PseudoIdentifier(..) => {
                             self.parse_leading_identifier()
                         }
@@ -1582,6 +3122,14 @@ impl Parser {
                             self.parse_switch()
                         }
                         Token::Throw => self.parse_throw().map(nodes::Expression::Throw),
+                        Token::Yield => self.parse_yield().map(nodes::Expression::Yield),
+                        Token::Timeout => self.fail(
+                            "`timeout` can only be used as a case of a `select`, to handle \
+                             no message arriving in time",
+                        ),
+                        Token::OverloadableInfixOperator(OverloadableInfixOperator::Subtract) => {
+                            self.parse_negation()
+                        }
 
                         non_expression => self.unexpected(non_expression),
                     })
@@ -1592,35 +3140,134 @@ impl Parser {
                  finished\
                  ",
             ),
-        }?;
+        };
 
-        match self.peek() {
-            Some(Token::Grouping(Grouping::OpenParentheses)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::Grouping(Grouping::OpenSquareBracket)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_typed_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::OverloadableSliceOperator(multiphase::OverloadableSliceOperator::Open)) => {
-                Ok(Expression::Operator(Operator::MultiSlice(
-                    self.parse_slice()?,
-                )))
+        if let Ok(expression) = &expression {
+            self.record_span(start, || nodes::Node::Expression(expression.clone()));
+        }
+        expression
+    }
+
+    /// A `-` where an operand is expected negates it, e.g. `-x` or `-(a + b)`,
+    /// whereas one after an operand is a subtraction. Negation binds tighter
+    /// than any infix operator but looser than calls and member accesses, so
+    /// `-a.b * c` is `(-(a.b)) * c`. Negative number literals are instead
+    /// lexed whole.
+    fn parse_negation(&mut self) -> Result<nodes::Expression> {
+        self.nested(|parser| {
+            parser.tokens.discard();
+            let operand = parser.parse_operand()?;
+            let operand = parser.parse_trailers(operand)?;
+            Ok(Expression::Operator(Operator::Prefix(
+                PrefixOperator::Negate,
+                Box::new(operand),
+            )))
+        })
+    }
+
+    /// Parse what can trail an already-parsed expression, such as calls, member
+    /// accesses, and operators.
+    fn parse_rest_of_expression(
+        &mut self,
+        expression: nodes::Expression,
+    ) -> Result<nodes::Expression> {
+        let mut expression = self.parse_trailers(expression)?;
+
+        loop {
+            match self.peek() {
+                // Pipes are left-associative so that `x |> f |> g` calls `g`
+                // with the result of `f`, which needs only the target of each
+                // pipe rather than everything after it.
+                Some(Token::OverloadableInfixOperator(OverloadableInfixOperator::Pipe)) => {
+                    self.tokens.discard();
+                    let operand = self.parse_operand()?;
+                    let target = self.parse_trailers(operand)?;
+                    expression = pipe(expression, target);
+                }
+                Some(Token::OverloadableInfixOperator(OverloadableInfixOperator::Compose)) => {
+                    self.tokens.discard();
+                    break Ok(Expression::Operator(Operator::Compose(
+                        Box::new(expression),
+                        Box::new(self.parse_expression()?),
+                    )));
+                }
+                Some(Token::OverloadableInfixOperator(operator)) => {
+                    self.tokens.discard();
+                    break Ok(Expression::Operator(Operator::OverloadableInfix(
+                        Box::new(expression),
+                        operator,
+                        Box::new(self.parse_expression()?),
+                    )));
+                }
+                _ => break Ok(expression),
             }
-            Some(Token::PostfixOperator(operator)) => Ok(Expression::Operator(
-                nodes::Operator::Postfix(Box::new(expression), operator),
-            )),
-            Some(Token::OverloadableInfixOperator(operator)) => {
-                Ok(Expression::Operator(Operator::OverloadableInfix(
+        }
+    }
+
+    /// Parse calls, member accesses, and postfix operators trailing an
+    /// expression. They chain, e.g. `a?.b?()`.
+    fn parse_trailers(&mut self, mut expression: nodes::Expression) -> Result<nodes::Expression> {
+        loop {
+            expression = match self.peek() {
+                Some(Token::Grouping(Grouping::OpenParentheses)) => {
+                    nodes::Expression::BranchingAndJumping(
+                        nodes::BranchingAndJumping::ExpressionCall(
+                            self.parse_expression_call(expression)?,
+                        ),
+                    )
+                }
+                Some(Token::Grouping(Grouping::OpenSquareBracket))
+                    if self.square_brackets_precede_call() =>
+                {
+                    nodes::Expression::BranchingAndJumping(
+                        nodes::BranchingAndJumping::ExpressionCall(
+                            self.parse_typed_expression_call(expression)?,
+                        ),
+                    )
+                }
+                Some(Token::Grouping(Grouping::OpenSquareBracket)) => {
+                    self.parse_index(expression)?
+                }
+                Some(Token::Dot) => {
+                    self.tokens.discard();
+                    let member = self.parse_identifier()?;
+                    let access = Expression::MemberAccess(MemberAccess {
+                        target: Box::new(expression),
+                        member,
+                    });
+
+                    // Like a symbol, a member access such as `5.times` can
+                    // take a trailing block without parentheses.
+                    if self.trailing_block_follows() {
+                        let lambda = self.parse_trailing_block()?;
+                        nodes::Expression::BranchingAndJumping(
+                            nodes::BranchingAndJumping::ExpressionCall(nodes::ExpressionCall {
+                                target: Box::new(access),
+                                arguments: CallArguments {
+                                    type_arguments: vec![],
+                                    arguments: vec![ValueArgument {
+                                        label: ArgumentLabel::Positional,
+                                        value: Expression::Literal(nodes::Literal::Lambda(lambda)),
+                                    }],
+                                },
+                            }),
+                        )
+                    } else {
+                        access
+                    }
+                }
+                Some(Token::OverloadableSliceOperator(
+                    multiphase::OverloadableSliceOperator::Open,
+                )) => Expression::Operator(Operator::MultiSlice(
                     Box::new(expression),
-                    operator,
-                    Box::new(self.parse_expression()?),
-                )))
-            }
-            _ => Ok(expression),
+                    self.parse_slice()?,
+                )),
+                Some(Token::PostfixOperator(operator)) => {
+                    self.tokens.discard();
+                    Expression::Operator(nodes::Operator::Postfix(Box::new(expression), operator))
+                }
+                _ => break Ok(expression),
+            };
         }
     }
 
@@ -1629,10 +3276,15 @@ impl Parser {
     /// parsing unambiguous without requiring explicit line continuations.
     fn parse_outermost_expression(&mut self) -> Result<nodes::Expression> {
         let token = self.tokens.peek().cloned();
+        let start = token.as_ref().map(|lexed| lexed.position);
         let expression = match token {
             Some(lexed) => {
                 let token = lexed.token;
-                self.parse_literal(token.clone())
+                let literal = self.parse_literal(token.clone());
+                if literal.is_some() {
+                    self.tokens.discard();
+                }
+                literal
                     .map(|literal| Ok(nodes::Expression::Literal(literal)))
                     .unwrap_or_else(|| match token {
                         // Non-atomic tokens each delegate to a dedicated method.
@@ -1650,6 +3302,12 @@ impl Parser {
                                 )
                             })
                         }
+                        Token::PseudoIdentifier(PseudoIdentifier::Continue) => {
+                            self.parse_continue().map(nodes::Expression::Continue)
+                        }
+                        Token::BranchingAndJumping(BranchingAndJumping::Break) => {
+                            self.parse_break().map(nodes::Expression::Break)
+                        }
                         Token::Identifier(..) | Token::PseudoIdentifier(..) => {
                             self.parse_leading_identifier()
                         }
@@ -1664,6 +3322,14 @@ impl Parser {
                             self.parse_switch()
                         }
                         Token::Throw => self.parse_throw().map(nodes::Expression::Throw),
+                        Token::Yield => self.parse_yield().map(nodes::Expression::Yield),
+                        Token::Timeout => self.fail(
+                            "`timeout` can only be used as a case of a `select`, to handle \
+                             no message arriving in time",
+                        ),
+                        Token::OverloadableInfixOperator(OverloadableInfixOperator::Subtract) => {
+                            self.parse_negation()
+                        }
 
                         non_expression => self.unexpected(non_expression),
                     })
@@ -1676,64 +3342,198 @@ impl Parser {
             ),
         }?;
 
-        match self.peek() {
-            Some(Token::Grouping(Grouping::OpenParentheses)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::Grouping(Grouping::OpenSquareBracket)) => Ok(
-                nodes::Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
-                    self.parse_typed_expression_call(expression)?,
-                )),
-            ),
-            Some(Token::OverloadableSliceOperator(multiphase::OverloadableSliceOperator::Open)) => {
-                Ok(Expression::Operator(Operator::MultiSlice(
-                    self.parse_slice()?,
-                )))
-            }
-            Some(Token::PostfixOperator(operator)) => Ok(Expression::Operator(
-                nodes::Operator::Postfix(Box::new(expression), operator),
-            )),
-            Some(Token::OverloadableInfixOperator(operator)) => {
-                Ok(Expression::Operator(Operator::OverloadableInfix(
-                    Box::new(expression),
-                    operator,
-                    Box::new(self.parse_expression()?),
-                )))
-            }
-            _ => Ok(expression),
-        }
+        self.record_span(start, || nodes::Node::Expression(expression.clone()));
+        let expression = self.parse_rest_of_expression(expression)?;
+        self.record_span(start, || nodes::Node::Expression(expression.clone()));
+        Ok(expression)
     }
 
     fn parse_block(&mut self) -> Result<nodes::Block> {
+        self.nested(|parser| {
+            parser.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+            parser.parse_inside_block(vec![])
+        })
+    }
+
+    /// Parse a block after its opening brace, given any leading expressions
+    /// already parsed by the caller.
+    fn parse_inside_block(&mut self, mut expressions: Vec<Expression>) -> Result<nodes::Block> {
         let mut bindings = vec![];
-        let mut expressions = vec![];
 
-        self.expect_and_discard(Token::Grouping(Grouping::OpenBrace))?;
+        // Only an expression coming last yields a value; a trailing binding
+        // leaves the block without a tail.
+        let mut ends_with_expression = !expressions.is_empty();
+
         loop {
+            // Blocks cannot declare new types like packages can, so give a
+            // clearer error than the declaration failing to parse as an
+            // expression.
+            let declaration = self.peek().as_ref().and_then(package_level_declaration);
+            if let Some(keyword) = declaration {
+                let position = self.tokens.peek().map(|lexed| lexed.position);
+                self.fail_at(
+                    position,
+                    format!(
+                        "`{}` declarations can only appear at the top level of a package, not inside a block",
+                        keyword
+                    ),
+                )?;
+            }
+
             if self.next_is(&Token::Binding(Binding::Var)) {
                 bindings.push(self.parse_local_var_binding()?);
+                ends_with_expression = false;
             } else if self.next_is(&Token::Grouping(Grouping::CloseBrace)) {
                 self.tokens.discard();
                 break;
             } else {
                 expressions.push(self.parse_outermost_expression()?);
+                ends_with_expression = true;
             }
         }
 
+        let tail = if ends_with_expression {
+            expressions.pop().map(Box::new)
+        } else {
+            None
+        };
+
         Ok(Block {
             expressions,
+            tail,
             bindings,
             parent: Some(Rc::new(Block::within(&self.current_scope))),
         })
     }
 
     fn parse_grouped_expression(&mut self) -> Result<nodes::Expression> {
+        let is_record = self.match_nth(1, |t| matches!(t, Token::Identifier(..)))
+            && self.nth_is(2, &Token::Colon);
+        if is_record {
+            let fields = self.parse_value_argument_list()?;
+            let all_labelled = fields
+                .iter()
+                .all(|field| matches!(field.label, ArgumentLabel::Labelled(..)));
+            if !all_labelled {
+                self.fail("every field of a record literal needs a label")?;
+            }
+            return Ok(Expression::RecordLiteral(nodes::RecordLiteral {
+                record_type: None,
+                fields,
+            }));
+        }
+
+        let position = self.tokens.peek().map(|lexed| lexed.position);
         self.tokens.discard();
         let expression = self.parse_expression()?;
-        self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
-        Ok(expression)
+        if !self.next_is(&Token::SubItemSeparator) {
+            self.expect_and_discard(Token::Grouping(Grouping::CloseParentheses))?;
+            return Ok(expression);
+        }
+
+        let mut elements = vec![expression];
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                if elements.len() < 2 {
+                    break self.fail_at(
+                        position,
+                        "a tuple needs at least two elements; without the trailing comma, a \
+                         single expression in parentheses is merely grouped",
+                    );
+                }
+                self.tokens.discard();
+                break Ok(Expression::Tuple(elements));
+            }
+            self.expect_and_discard(Token::SubItemSeparator)?;
+            if !self.next_is(&Token::Grouping(Grouping::CloseParentheses)) {
+                elements.push(self.parse_expression()?);
+            }
+        }
+    }
+
+    /// A `[` can only start a list literal in operand position; after an
+    /// operand, it instead starts type arguments.
+    fn parse_list_literal(&mut self) -> Result<nodes::Expression> {
+        self.tokens.discard();
+
+        let mut elements = vec![];
+        loop {
+            if self.next_is(&Token::Grouping(Grouping::CloseSquareBracket)) {
+                self.tokens.discard();
+                break Ok(Expression::ListLiteral(elements));
+            }
+            elements.push(self.parse_expression()?);
+            if !self.next_is(&Token::Grouping(Grouping::CloseSquareBracket)) {
+                self.expect_and_discard(Token::SubItemSeparator)?;
+            }
+        }
+    }
+
+    /// Parse the item that `token` starts, if it starts one at all.
+    fn parse_item(&mut self, token: &Token) -> Option<Result<Item>> {
+        if !starts_item(token) {
+            return None;
+        }
+
+        let start = self.tokens.peek().map(|lexed| lexed.position);
+        let item = match token {
+            Token::DeclarationHead(DeclarationHead::Class) => {
+                self.parse_class_definition().map(Item::Type)
+            }
+            Token::DeclarationHead(DeclarationHead::Enum) => {
+                self.parse_enum_definition().map(Item::Type)
+            }
+            Token::DeclarationHead(DeclarationHead::Extend) => {
+                self.parse_extension().map(Item::Extension)
+            }
+            Token::DeclarationHead(DeclarationHead::Interface) => {
+                self.parse_interface_definition().map(Item::Type)
+            }
+            Token::DeclarationHead(DeclarationHead::Package) => {
+                self.parse_package_definition().map(Item::Package)
+            }
+            Token::Binding(Binding::Final) | Token::DeclarationHead(DeclarationHead::Extern) => {
+                self.parse_binding().map(Item::Final)
+            }
+            Token::Macros(Macros::Reader) => self
+                .parse_reader_definition()
+                .map(|reader| Item::Macro(nodes::Macro::ReaderDefinition(reader))),
+            Token::Macros(Macros::At) => self.parse_annotated_item(),
+            _ => self.parse_fun().map(Item::Fun),
+        };
+        if let Ok(item) = &item {
+            self.record_span(start, || nodes::Node::Item(item.clone()));
+        }
+        Some(item)
+    }
+
+    /// Annotations precede an item, such as `@inline` in `@inline fun f() { }`,
+    /// and can take arguments like a call, as in `@route("/path")`.
+    fn parse_annotated_item(&mut self) -> Result<Item> {
+        let mut annotations = vec![];
+        while self.next_is(&Token::Macros(Macros::At)) {
+            self.tokens.discard();
+            let symbol = self.parse_symbol()?;
+            let arguments = if self.next_is(&Token::Grouping(Grouping::OpenParentheses)) {
+                self.parse_value_argument_list()?
+            } else {
+                vec![]
+            };
+            annotations.push(nodes::Annotation { symbol, arguments });
+        }
+
+        let item = match self.peek() {
+            Some(token) => match self.parse_item(&token) {
+                Some(item) => item?,
+                None => self.fail("annotations must be followed by the item they annotate")?,
+            },
+            None => self.premature_eof()?,
+        };
+
+        Ok(Item::Annotated(nodes::Annotated {
+            annotations,
+            item: Box::new(item),
+        }))
     }
 
     fn parse_inside_package(&mut self) -> Result<Vec<nodes::Item>> {
@@ -1745,33 +3545,10 @@ impl Parser {
             match maybe_token {
                 None => break,
 
-                Some(token) => match token {
-                    Token::DeclarationHead(DeclarationHead::Class) => {
-                        let class_definition = self.parse_class_definition()?;
-                        items.push(Item::Type(class_definition));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Extend) => {
-                        let extension = self.parse_extension()?;
-                        items.push(Item::Extension(extension));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Interface) => {
-                        let interface = self.parse_interface_definition()?;
-                        items.push(Item::Type(interface));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Package) => {
-                        let package = self.parse_package_definition()?;
-                        items.push(Item::Package(package));
-                    }
-                    Token::DeclarationHead(DeclarationHead::Fun) => {
-                        let fun = self.parse_fun()?;
-                        items.push(Item::Fun(fun));
-                    }
-                    Token::Binding(Binding::Final) => {
-                        let binding = self.parse_binding()?;
-                        items.push(Item::Final(binding));
-                    }
-
-                    unexpected => self.unexpected(unexpected)?,
+                Some(Token::Grouping(Grouping::CloseBrace)) => break,
+                Some(token) => match self.parse_item(&token) {
+                    Some(item) => items.push(item?),
+                    None => self.unexpected(token)?,
                 },
             }
         }
@@ -1779,14 +3556,21 @@ impl Parser {
         Ok(items)
     }
 
-    fn parse_main_package(&mut self) -> Result<nodes::MainPackage> {
-        let mut items: Vec<Item> = vec![];
-
-        let mut implicit_main = Block::new_root();
-
+    fn parse_main_package_header(&mut self) -> Result<(Identifier, Vec<nodes::Import>)> {
         self.expect_and_discard(Token::DeclarationHead(DeclarationHead::Package))?;
 
+        let position = self.tokens.peek().map(|lexed| lexed.position);
         let name = self.parse_identifier()?;
+        if name != Identifier::from("main") {
+            self.fail_at(
+                position,
+                format!(
+                    "the package a program starts from must be named `main`, not `{}`",
+                    name.0
+                ),
+            )?;
+        }
+
         let has_imports = self.next_is(&Token::Grouping(Grouping::OpenParentheses));
         let imports = if has_imports {
             self.parse_imports()?
@@ -1794,55 +3578,48 @@ impl Parser {
             vec![]
         };
 
-        loop {
-            let maybe_token = self.tokens.peek().map(|lexed| lexed.token.clone());
+        Ok((name, imports))
+    }
 
-            match maybe_token {
-                None => break,
+    fn parse_main_package(&mut self) -> Result<nodes::MainPackage> {
+        let mut items: Vec<Item> = vec![];
 
-                Some(token) => {
-                    match token {
-                        Token::DeclarationHead(DeclarationHead::Class) => {
-                            let class_definition = self.parse_class_definition()?;
-                            items.push(Item::Type(class_definition));
-                        }
-                        Token::DeclarationHead(DeclarationHead::Extend) => {
-                            let extension = self.parse_extension()?;
-                            items.push(Item::Extension(extension));
-                        }
-                        Token::DeclarationHead(DeclarationHead::Interface) => {
-                            let interface = self.parse_interface_definition()?;
-                            items.push(Item::Type(interface));
-                        }
-                        Token::DeclarationHead(DeclarationHead::Package) => {
-                            let package = self.parse_package_definition()?;
-                            items.push(Item::Package(package));
-                        }
-                        Token::DeclarationHead(DeclarationHead::Fun) => {
-                            let fun = self.parse_fun()?;
-                            items.push(Item::Fun(fun));
-                        }
-                        Token::Binding(Binding::Final) => {
-                            let binding = self.parse_binding()?;
-                            items.push(Item::Final(binding));
-                        }
+        let mut implicit_main = Block::new_root();
 
-                        // Unlike all other packages, the main package allows both variables
-                        // without type annotations, falling back to type inference, and also
-                        // arbritary expressions.
-                        Token::Binding(Binding::Var) => {
-                            let binding = self.parse_local_var_binding()?;
-                            implicit_main.bindings.push(binding);
-                        }
-                        _ => {
-                            let expression = self.parse_expression()?;
-                            implicit_main.expressions.push(expression);
+        let (name, imports) = self.parse_main_package_header()?;
+
+        loop {
+            let maybe_token = self
+                .tokens
+                .peek()
+                .map(|lexed| (lexed.token.clone(), lexed.position));
+
+            match maybe_token {
+                None | Some((Token::Eof, _)) => break,
+
+                Some((token, start)) => {
+                    let is_item = starts_item(&token);
+                    let parsed =
+                        self.parse_main_package_entry(token, &mut items, &mut implicit_main);
+
+                    match parsed {
+                        Err(Error::Parser(error)) if self.recovering => {
+                            let end = self.skip_rest_of_line(start);
+                            let failure = nodes::ParseFailure { start, end, error };
+                            if is_item {
+                                items.push(Item::Error(failure));
+                            } else {
+                                implicit_main.expressions.push(Expression::Error(failure));
+                            }
                         }
+                        parsed => parsed?,
                     }
                 }
             }
         }
 
+        // The main package is where a program starts, so it is public rather
+        // than taking the default of other packages.
         let package = Package {
             items,
             accessibility: Accessibility::Public,
@@ -1857,6 +3634,77 @@ impl Parser {
         })
     }
 
+    fn parse_main_package_entry(
+        &mut self,
+        token: Token,
+        items: &mut Vec<Item>,
+        implicit_main: &mut Block,
+    ) -> Result<()> {
+        if let Some(item) = self.parse_item(&token) {
+            items.push(item?);
+            return Ok(());
+        }
+
+        match token {
+            // Unlike all other packages, the main package allows both variables
+            // without type annotations, falling back to type inference, and also
+            // arbritary expressions.
+            Token::Binding(Binding::Var) => {
+                let binding = self.parse_local_var_binding()?;
+                implicit_main.bindings.push(binding);
+            }
+            _ => {
+                let expression = self.parse_expression()?;
+                implicit_main.expressions.push(expression);
+            }
+        }
+        Ok(())
+    }
+
+    /// Skip the rest of a top-level line that failed to parse from `start`,
+    /// so that parsing can resume from the next one. A line is only left once
+    /// all brackets opened on it are closed. Returns where the last skipped
+    /// token starts.
+    fn skip_rest_of_line(&mut self, start: Position) -> Position {
+        let mut end = start;
+
+        // Skip at least the token that failed, if the failed parse did not
+        // consume it, so that recovery always makes progress.
+        let stuck = matches!(
+            self.tokens.peek(),
+            Some(lexed) if (lexed.position == start) && (lexed.token != Token::Eof)
+        );
+        if stuck {
+            self.tokens.discard();
+        }
+
+        loop {
+            let next = self.tokens.peek().map(|lexed| {
+                let starts_line = (lexed.bracket_depth == 0)
+                    && matches!(
+                        &lexed.trivia,
+                        Some(trivia) if trivia.contains(&['\n', '\r'][..])
+                    );
+                let closes_bracket = matches!(
+                    lexed.token,
+                    Token::Grouping(Grouping::CloseBrace)
+                        | Token::Grouping(Grouping::CloseParentheses)
+                        | Token::Grouping(Grouping::CloseSquareBracket)
+                );
+                let resumable = (lexed.token == Token::Eof) || (starts_line && !closes_bracket);
+                (resumable, lexed.position)
+            });
+
+            match next {
+                Some((false, position)) => {
+                    end = position;
+                    self.tokens.discard();
+                }
+                _ => break end,
+            }
+        }
+    }
+
     fn maybe_parse_shebang(&mut self) -> Option<multiphase::Shebang> {
         let maybe_line = {
             let token = &self.tokens.peek()?.token;
@@ -1872,6 +3720,25 @@ impl Parser {
         maybe_line
     }
 
+    /// Both type and value parameters are documented by a SyDoc before them.
+    /// One after them is also accepted, but not both.
+    fn parse_parameter_sydoc(&mut self, leading: Option<SyDoc>) -> Result<Option<SyDoc>> {
+        match (leading, self.maybe_parse_sydoc()) {
+            (Some(_), Some(_)) => self
+                .fail("a parameter can only have one SyDoc, either before or after the parameter"),
+            (leading, following) => Ok(leading.or(following)),
+        }
+    }
+
+    fn maybe_parse_sydoc(&mut self) -> Option<SyDoc> {
+        if let Some(Token::SyDoc(doc)) = self.peek() {
+            self.tokens.discard();
+            Some(doc)
+        } else {
+            None
+        }
+    }
+
     fn maybe_parse_version(&mut self) -> Option<Version> {
         let maybe_version = {
             let token = &self.tokens.peek()?.token;
@@ -1892,25 +3759,2622 @@ impl Parser {
         let version = self.maybe_parse_version();
         let main_package = self.parse_main_package();
 
+        let spans = self.spans.take().unwrap_or_default();
         main_package.map(|main| nodes::MainFile {
             shebang,
             version,
             package: main,
+            spans,
         })
     }
 
+    /// Limit how deeply expressions, patterns, and blocks can nest, in place
+    /// of the default of 128 levels.
+    pub fn with_max_nesting_depth(mut self, max_nesting_depth: usize) -> Self {
+        self.max_nesting_depth = max_nesting_depth;
+        self
+    }
+
+    /// Record the span of each item and expression parsed into the main file,
+    /// so that tooling can look nodes up by position with `nodes::node_at`.
+    pub fn with_spans(mut self) -> Self {
+        self.spans = Some(vec![]);
+        self
+    }
+
     /// Parse an AST from a lexer, ensuring the underlying lexer task has
     /// finished before continuing.
     pub fn parse(mut self) -> Result<nodes::MainFile> {
         let file = self.parse_main_file();
-        let join_handle = self.tokens.join_lexer_thread();
-        join_handle.map_err(|err| {
-            let description = ParserErrorDescription::LexerThreadFailed(format!(
-                "parsing failed due to not being able to join on the lexer thread: {:?}",
-                err,
-            ));
-            Error::Parser(ParserError { description })
-        })?;
+        self.join_lexer_thread()?;
         file
     }
+
+    fn join_lexer_thread(self) -> Result<()> {
+        let join_handle = self.tokens.join_lexer_thread();
+        join_handle.map_err(|err| match err {
+            LexerTaskError::Lexer(err) => Error::Lexer(err),
+            LexerTaskError::Task(_) => {
+                let description = ParserErrorDescription::LexerThreadFailed(format!(
+                    "parsing failed due to not being able to join on the lexer thread: {:?}",
+                    err,
+                ));
+                Error::Parser(ParserError {
+                    description,
+                    position: None,
+                })
+            }
+        })
+    }
+
+    /// Parse a lone expression rather than a whole file, such as for a REPL or
+    /// a constant evaluator. Nothing can follow the expression. Like `parse`,
+    /// the underlying lexer task is finished before returning.
+    pub fn parse_single_expression(mut self) -> Result<nodes::Expression> {
+        let expression = self.parse_expression().and_then(|expression| {
+            match self.peek() {
+                Some(Token::Eof) | None => {}
+                Some(trailing) => self.unexpected(trailing)?,
+            }
+            Ok(expression)
+        });
+        self.join_lexer_thread()?;
+        expression
+    }
+
+    /// Parse an AST like `parse`, but return a best-effort tree despite parse
+    /// errors in the main package. Each top-level line that fails to parse
+    /// becomes an error node carrying what went wrong. Lexer errors still fail
+    /// the whole parse.
+    pub fn parse_recovering(mut self) -> Result<nodes::MainFile> {
+        self.recovering = true;
+        self.parse()
+    }
+
+    /// Parse just the imports of a file, stopping before the body of its main
+    /// package. This lets tools such as build systems work out dependencies
+    /// without a full parse.
+    ///
+    /// Unlike `parse`, this does not join the lexer thread; the thread stops
+    /// once it finds nothing is listening for its tokens anymore.
+    pub fn parse_imports_only(mut self) -> Result<Vec<nodes::Import>> {
+        self.maybe_parse_shebang();
+        self.maybe_parse_version();
+        let (_, imports) = self.parse_main_package_header()?;
+        Ok(imports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+    use std::fmt::Debug;
+    use std::hash::{Hash, Hasher};
+
+    use super::*;
+    use crate::lexing::lexer::Lexer;
+    use crate::parsing::nodes::{Import, ImportSingleStem, ImportStem};
+    use crate::source::in_memory::Source;
+
+    fn test_parser(s: &str) -> Parser {
+        let source_chars = s.chars().collect::<Vec<char>>();
+        let tokens = Tokens::from(Lexer::from(Source::from(source_chars))).unwrap();
+        Parser::from(tokens)
+    }
+
+    fn relative(identifiers: &[&'static str]) -> Symbol {
+        Symbol::Relative(SymbolLookup(
+            identifiers.iter().cloned().map(Identifier::from).collect(),
+        ))
+    }
+
+    #[test]
+    fn single_expressions() {
+        let number = |n| {
+            Box::new(Expression::Literal(nodes::Literal::Number(
+                multiphase::Number(n, 0, 0),
+            )))
+        };
+
+        assert_eq!(
+            test_parser("1 + 2 * 3").parse_single_expression().unwrap(),
+            Expression::Operator(Operator::OverloadableInfix(
+                number(1),
+                OverloadableInfixOperator::Add,
+                Box::new(Expression::Operator(Operator::OverloadableInfix(
+                    number(2),
+                    OverloadableInfixOperator::Multiply,
+                    number(3),
+                ))),
+            )),
+        );
+
+        match test_parser("1 + 2 3").parse_single_expression() {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Unexpected(token),
+                ..
+            })) => assert_eq!(
+                token,
+                Token::Literal(Literal::Number(multiphase::Number(3, 0, 0)))
+            ),
+            other => panic!("expected an unexpected trailing token but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn aliased_import() {
+        let mut parser = test_parser("(foo.bar as baz)");
+        let imports = parser.parse_imports().unwrap();
+
+        assert_eq!(
+            imports,
+            vec![Import {
+                root: Some(relative(&["foo"])),
+                stem: ImportStem::Single(ImportSingleStem {
+                    name: Identifier::from("bar"),
+                    alias: Some(Identifier::from("baz")),
+                    readers: vec![],
+                }),
+            }],
+        );
+    }
+
+    #[test]
+    fn imports_with_readers() {
+        let mut parser = test_parser("(module1.lisp with reader (package1.readAsLisp))");
+        let imports = parser.parse_imports().unwrap();
+
+        assert_eq!(
+            imports,
+            vec![Import {
+                root: Some(relative(&["module1"])),
+                stem: ImportStem::Single(ImportSingleStem {
+                    name: Identifier::from("lisp"),
+                    alias: None,
+                    readers: vec![relative(&["package1", "readAsLisp"])],
+                }),
+            }],
+        );
+    }
+
+    #[test]
+    fn reader_definitions() {
+        let mut parser = test_parser("reader public lisp = readAsLisp for \"(\"");
+        let reader = parser.parse_reader_definition().unwrap();
+
+        assert_eq!(reader.accessibility, Accessibility::Public);
+        assert_eq!(reader.name, Identifier::from("lisp"));
+        assert_eq!(reader.r#macro, relative(&["readAsLisp"]));
+        assert_eq!(reader.trigger, multiphase::SylanString::from("("));
+
+        let mut parser = test_parser("reader lisp = readAsLisp");
+        assert!(parser.parse_reader_definition().is_err());
+    }
+
+    #[test]
+    fn aliased_leaf_in_import_group() {
+        let mut parser = test_parser("(foo { bar, baz as qux })");
+        let imports = parser.parse_imports().unwrap();
+
+        assert_eq!(
+            imports,
+            vec![Import {
+                root: Some(relative(&["foo"])),
+                stem: ImportStem::Multiple(vec![
+                    Import {
+                        root: Some(relative(&[])),
+                        stem: ImportStem::Single(ImportSingleStem {
+                            name: Identifier::from("bar"),
+                            alias: None,
+                            readers: vec![],
+                        }),
+                    },
+                    Import {
+                        root: Some(relative(&[])),
+                        stem: ImportStem::Single(ImportSingleStem {
+                            name: Identifier::from("baz"),
+                            alias: Some(Identifier::from("qux")),
+                            readers: vec![],
+                        }),
+                    },
+                ]),
+            }],
+        );
+    }
+
+    #[test]
+    fn imports_only() {
+        let parser = test_parser(
+            "#!/usr/bin/env sylan\nv1.0\npackage main (foo.bar, baz { qux }, quux)\n}}}",
+        );
+        let imports = parser.parse_imports_only().unwrap();
+
+        let single = |root: &[&'static str], name| Import {
+            root: Some(relative(root)),
+            stem: ImportStem::Single(ImportSingleStem {
+                name: Identifier::from(name),
+                alias: None,
+                readers: vec![],
+            }),
+        };
+
+        assert_eq!(
+            imports,
+            vec![
+                single(&["foo"], "bar"),
+                Import {
+                    root: Some(relative(&["baz"])),
+                    stem: ImportStem::Multiple(vec![single(&[], "qux")]),
+                },
+                single(&[], "quux"),
+            ],
+        );
+    }
+
+    #[test]
+    fn version_guarded_imports() {
+        let mut parser = test_parser("(foo, if v2 { bar })");
+        let imports = parser.parse_imports().unwrap();
+
+        let single = |name| Import {
+            root: Some(relative(&[])),
+            stem: ImportStem::Single(ImportSingleStem {
+                name: Identifier::from(name),
+                alias: None,
+                readers: vec![],
+            }),
+        };
+
+        assert_eq!(
+            imports,
+            vec![
+                single("foo"),
+                Import {
+                    root: None,
+                    stem: ImportStem::VersionGuarded(
+                        Version {
+                            major: 2,
+                            minor: 0,
+                            patch: 0,
+                        },
+                        vec![single("bar")],
+                    ),
+                },
+            ],
+        );
+
+        let guarded = test_parser("(if v2.1 { bar })").parse_imports().unwrap();
+        assert_eq!(
+            guarded[0].stem,
+            ImportStem::VersionGuarded(
+                Version {
+                    major: 2,
+                    minor: 1,
+                    patch: 0,
+                },
+                vec![single("bar")],
+            ),
+        );
+        assert_described_error(
+            test_parser("(if foo { bar })").parse_imports(),
+            "must be a version",
+        );
+
+        // Past a file's header, names like `v2` are ordinary identifiers.
+        assert!(matches!(
+            test_parser("1 + v2").parse_expression().unwrap(),
+            Expression::Operator(Operator::OverloadableInfix(..)),
+        ));
+    }
+
+    #[test]
+    fn aliased_import_group() {
+        let mut parser = test_parser("(foo { bar } as baz)");
+        assert_described_error(parser.parse_imports(), "import groups cannot be aliased");
+    }
+
+    #[test]
+    fn block_tail() {
+        let mut parser = test_parser("{ foo bar baz }");
+        let block = parser.parse_block().unwrap();
+
+        assert_eq!(
+            block.expressions,
+            vec![
+                Expression::Symbol(relative(&["foo"])),
+                Expression::Symbol(relative(&["bar"])),
+            ],
+        );
+        assert_eq!(
+            block.tail,
+            Some(Box::new(Expression::Symbol(relative(&["baz"])))),
+        );
+    }
+
+    #[test]
+    fn block_ending_in_binding_has_no_tail() {
+        let mut parser = test_parser("{ foo var bar = baz }");
+        let block = parser.parse_block().unwrap();
+
+        assert_eq!(
+            block.expressions,
+            vec![Expression::Symbol(relative(&["foo"]))]
+        );
+        assert_eq!(block.bindings.len(), 1);
+        assert_eq!(block.tail, None);
+    }
+
+    #[test]
+    fn local_binding_type_annotations() {
+        let block = test_parser("{ var x = 1 var y Int = 1 }")
+            .parse_block()
+            .unwrap();
+        let one = Box::new(Expression::Literal(nodes::Literal::Number(
+            multiphase::Number(1, 0, 0),
+        )));
+        let binding = |name, explicit_type_annotation| nodes::Binding {
+            pattern: Pattern {
+                item: PatternItem::Identifier(Identifier::from(name)),
+                bound_match: None,
+            },
+            value: Some(one.clone()),
+            explicit_type_annotation,
+        };
+
+        assert_eq!(
+            block.bindings,
+            vec![
+                binding("x", None),
+                binding("y", Some(TypeReference::new(relative(&["Int"])))),
+            ],
+        );
+    }
+
+    #[test]
+    fn operator_funs() {
+        let signature = |source| test_parser(source).parse_fun().unwrap().signature;
+
+        let index = signature("fun operator [||] (n Usize) { }");
+        assert_eq!(index.name, Identifier::from("[||]"));
+        assert_eq!(index.operator, Some(OverloadedOperator::Index));
+        assert_eq!(index.value_parameters.len(), 1);
+
+        let slice = signature("fun public operator [|:|] (slice Slice) { }");
+        assert_eq!(slice.name, Identifier::from("[|:|]"));
+        assert_eq!(slice.operator, Some(OverloadedOperator::Slice));
+
+        let multi_slice = signature("fun public operator [|:...|] (fragments SliceFragment) { }");
+        assert_eq!(multi_slice.name, Identifier::from("[|:...|]"));
+        assert_eq!(multi_slice.operator, Some(OverloadedOperator::MultiSlice));
+
+        let add = signature("fun public operator + (that This) This { }");
+        assert_eq!(add.name, Identifier::from("+"));
+        assert_eq!(
+            add.operator,
+            Some(OverloadedOperator::Infix(OverloadableInfixOperator::Add)),
+        );
+
+        assert_eq!(signature("fun plain() { }").operator, None);
+        assert_described_error(
+            test_parser("fun operator plus(that This) This { }").parse_fun(),
+            "named by the operator it overloads",
+        );
+    }
+
+    #[test]
+    fn fun_return_types() {
+        let bare = test_parser("fun foo() Int { }").parse_fun().unwrap();
+        let arrowed = test_parser("fun foo() -> Int { }").parse_fun().unwrap();
+
+        assert_eq!(
+            bare.signature.return_type,
+            Some(ReturnType {
+                r#type: TypeReference::new(relative(&["Int"])),
+                ignorable: false,
+            }),
+        );
+        assert_eq!(bare.signature, arrowed.signature);
+    }
+
+    #[test]
+    fn select_timeout_with_duration() {
+        let mut parser = test_parser("select Foo { timeout 5.seconds { } }");
+        let timeout = parser.parse_select().unwrap().timeout.unwrap();
+
+        assert_eq!(
+            *timeout.nanoseconds,
+            Expression::MemberAccess(MemberAccess {
+                target: Box::new(Expression::Literal(nodes::Literal::Number(
+                    multiphase::Number(5, 0, 0)
+                ))),
+                member: Identifier::from("seconds"),
+            }),
+        );
+    }
+
+    #[test]
+    fn select_timeout_with_plain_number() {
+        let mut parser = test_parser("select Foo { timeout 1000 { } }");
+        let timeout = parser.parse_select().unwrap().timeout.unwrap();
+
+        assert_eq!(
+            *timeout.nanoseconds,
+            Expression::Literal(nodes::Literal::Number(multiphase::Number(1000, 0, 0))),
+        );
+    }
+
+    fn assert_described_error<T: Debug>(result: Result<T>, containing: &str) {
+        match result {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(description),
+                ..
+            })) => assert!(description.contains(containing), "{}", description),
+            other => panic!("expected an error about {} but got {:?}", containing, other),
+        }
+    }
+
+    #[test]
+    fn premature_eof_position() {
+        let source = "package main\n\nfun f() Int {\n    1 +";
+        let chars = source.chars().collect::<Vec<char>>();
+        let tokens = Tokens::from(Lexer::from(Source::from(chars))).unwrap();
+        let end = tokens.collect_all().unwrap().last().unwrap().position;
+
+        match test_parser(source).parse_main_package() {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::PrematureEof,
+                position,
+            })) => assert_eq!(position, Some(end)),
+            other => panic!("expected a premature EOF but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn maximum_nesting_depth() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        let message = "maximum nesting depth exceeded";
+
+        assert!(test_parser(&nested(100)).parse_expression().is_ok());
+        assert_described_error(test_parser(&nested(10_000)).parse_expression(), message);
+
+        let limited = |source: &str| test_parser(source).with_max_nesting_depth(3);
+        assert!(limited(&nested(2)).parse_expression().is_ok());
+        assert_described_error(limited(&nested(3)).parse_expression(), message);
+
+        // Blocks count towards the depth too.
+        let block = |max| test_parser("{ f((1)) }").with_max_nesting_depth(max);
+        assert!(block(3).parse_block().is_ok());
+        assert_described_error(block(2).parse_block(), message);
+
+        // So do negations and type arguments, which recurse without going
+        // through a full expression.
+        let negations = format!("{}x", "- ".repeat(100_000));
+        assert_described_error(test_parser(&negations).parse_expression(), message);
+        assert!(test_parser("- - x").parse_expression().is_ok());
+
+        let types = |depth: usize| format!("{}Int{}", "List[".repeat(depth), "]".repeat(depth));
+        assert!(test_parser(&types(100)).parse_type_reference().is_ok());
+        assert_described_error(test_parser(&types(100_000)).parse_type_reference(), message);
+    }
+
+    #[test]
+    fn declarations_inside_blocks() {
+        for source in &[
+            "-> { class Foo { } }",
+            "-> x { class Foo { } }",
+            "-> { 1\ninterface Foo { } }",
+            "-> { var x = 1\npackage foo { } }",
+        ] {
+            assert_described_error(test_parser(source).parse_expression(), "not inside a block");
+        }
+
+        assert_described_error(
+            test_parser("fun f() { enum E { } }").parse_fun(),
+            "`enum` declarations can only appear at the top level of a package",
+        );
+    }
+
+    #[test]
+    fn stray_timeout() {
+        assert_described_error(
+            test_parser("timeout 5 { }").parse_expression(),
+            "case of a `select`",
+        );
+    }
+
+    #[test]
+    fn select_with_two_timeouts() {
+        assert_described_error(
+            test_parser("select Foo { timeout 1 { } timeout 2 { } }").parse_select(),
+            "only have one `timeout`",
+        );
+    }
+
+    #[test]
+    fn cond_case_with_multiple_conditions() {
+        let mut parser = test_parser("switch { a, b { x } c { y } }");
+        let cases = match parser.parse_switch().unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Cond(Cond(cases))) => cases,
+            other => panic!("expected a cond but got {:?}", other),
+        };
+
+        let conditions = cases
+            .iter()
+            .map(|case| case.conditions.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            conditions,
+            vec![
+                vec![
+                    Expression::Symbol(relative(&["a"])),
+                    Expression::Symbol(relative(&["b"])),
+                ],
+                vec![Expression::Symbol(relative(&["c"]))],
+            ],
+        );
+        assert_eq!(
+            cases[0].then.tail,
+            Some(Box::new(Expression::Symbol(relative(&["x"])))),
+        );
+    }
+
+    #[test]
+    fn cond_case_without_conditions() {
+        let mut parser = test_parser("switch { a { x } { y } }");
+        match parser.parse_switch() {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(_),
+                ..
+            })) => {}
+            other => panic!("expected a described parser error but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn operator_after_if() {
+        let expression = test_parser("(if c { 1 } else { 2 }) + 3")
+            .parse_expression()
+            .unwrap();
+
+        match expression {
+            Expression::Operator(Operator::OverloadableInfix(
+                left,
+                OverloadableInfixOperator::Add,
+                right,
+            )) => {
+                assert!(matches!(
+                    *left,
+                    Expression::BranchingAndJumping(nodes::BranchingAndJumping::If(_)),
+                ));
+                assert_eq!(
+                    *right,
+                    Expression::Literal(nodes::Literal::Number(multiphase::Number(3, 0, 0))),
+                );
+            }
+            other => panic!("expected an addition but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn switch_as_operand() {
+        let expression = test_parser("switch x { 1 { 2 } _ { 3 } } * 4")
+            .parse_expression()
+            .unwrap();
+
+        match expression {
+            Expression::Operator(Operator::OverloadableInfix(
+                left,
+                OverloadableInfixOperator::Multiply,
+                right,
+            )) => {
+                assert!(matches!(
+                    *left,
+                    Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(_)),
+                ));
+                assert_eq!(
+                    *right,
+                    Expression::Literal(nodes::Literal::Number(multiphase::Number(4, 0, 0))),
+                );
+            }
+            other => panic!("expected a multiplication but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn alternative_case_patterns() {
+        let switch = match test_parser("switch x { 1, 2, 3 { a } 4 { b } }").parse_expression() {
+            Ok(Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch))) => {
+                switch
+            }
+            other => panic!("expected a switch but got {:?}", other),
+        };
+        let select = test_parser("select Int { 1, 2 if ready { a } _ { b } }")
+            .parse_select()
+            .unwrap();
+
+        let number = |n| PatternItem::Literal(nodes::Literal::Number(multiphase::Number(n, 0, 0)));
+        let patterns = |case: &Case| {
+            case.matches
+                .iter()
+                .map(|case_match| case_match.pattern.item.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(switch.cases.len(), 2);
+        assert_eq!(
+            patterns(&switch.cases[0]),
+            vec![number(1), number(2), number(3)]
+        );
+        assert_eq!(patterns(&switch.cases[1]), vec![number(4)]);
+
+        assert_eq!(select.cases.len(), 2);
+        assert_eq!(patterns(&select.cases[0]), vec![number(1), number(2)]);
+        assert_eq!(select.cases[0].matches[0].guard, None);
+        assert!(select.cases[0].matches[1].guard.is_some());
+        assert_eq!(patterns(&select.cases[1]), vec![PatternItem::Ignored]);
+    }
+
+    #[test]
+    fn unreachable_cases() {
+        let message = "a `_` case matches everything, so cases after it can never be reached";
+
+        assert!(test_parser("switch x { 1 { a } _ { b } }")
+            .parse_expression()
+            .is_ok());
+        assert!(test_parser("switch x { _ if ready { a } 1 { b } }")
+            .parse_expression()
+            .is_ok());
+        assert!(test_parser("select Int { _ { a } timeout 1 { b } }")
+            .parse_select()
+            .is_ok());
+
+        assert_described_error(
+            test_parser("switch x { _ { a } 1 { b } }").parse_expression(),
+            message,
+        );
+        assert_described_error(
+            test_parser("select Int { 1, _ { a } 2 { b } }").parse_select(),
+            message,
+        );
+    }
+
+    #[test]
+    fn duplicate_explicit_parameter_labels() {
+        match test_parser("fun foo(a x Int, a y Int) { }").parse_fun() {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(message),
+                ..
+            })) => assert!(message.contains("`a`")),
+            other => panic!("expected a described parser error but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn duplicate_implicit_parameter_label() {
+        match test_parser("-> x, x y { y }").parse_expression() {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(message),
+                ..
+            })) => assert!(message.contains("`x`")),
+            other => panic!("expected a described parser error but got {:?}", other),
+        }
+    }
+
+    fn parse_enum_variants(source: &str) -> Vec<nodes::EnumVariant> {
+        match test_parser(source).parse_enum_definition().unwrap().item {
+            nodes::TypeItem::Enum(nodes::Enum { variants, .. }) => variants,
+            other => panic!("expected an enum but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn enum_variants() {
+        let variants = parse_enum_variants("enum Message(Stop, Get(to recipient Task))");
+
+        assert_eq!(variants[0].name, Identifier::from("Stop"));
+        assert!(variants[0].parameters.is_empty());
+        assert_eq!(
+            variants[1].parameters,
+            vec![nodes::EnumVariantParameter {
+                label: Some(Identifier::from("to")),
+                name: Identifier::from("recipient"),
+                type_annotation: TypeReference::new(relative(&["Task"])),
+            }],
+        );
+    }
+
+    #[test]
+    fn auto_assigned_enum_discriminants() {
+        let variants = parse_enum_variants("enum Colour(Red, Green, Blue)");
+
+        assert_eq!(variants.len(), 3);
+        assert!(variants
+            .iter()
+            .all(|variant| variant.discriminant.is_none()));
+        assert_eq!(
+            variants
+                .iter()
+                .map(|variant| variant.discriminant_value)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2],
+        );
+    }
+
+    #[test]
+    fn largest_enum_discriminant() {
+        let variants = parse_enum_variants("enum E(A: 9223372036854775807)");
+        assert_eq!(variants[0].discriminant_value, i64::MAX);
+
+        let variants = parse_enum_variants("enum E(A: 9223372036854775807, B: 0)");
+        assert_eq!(variants[1].discriminant_value, 0);
+
+        assert_described_error(
+            test_parser("enum E(A: 9223372036854775807, B)").parse_enum_definition(),
+            "the enum variant `B` cannot be given a discriminant",
+        );
+    }
+
+    #[test]
+    fn explicit_enum_discriminants() {
+        let variants = parse_enum_variants("enum Colour(Red: 1, Green: 2, Blue)");
+
+        assert_eq!(
+            variants
+                .iter()
+                .map(|variant| variant.discriminant_value)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3],
+        );
+
+        let discriminants = variants
+            .into_iter()
+            .map(|variant| variant.discriminant)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            discriminants,
+            vec![
+                Some(Expression::Literal(nodes::Literal::Number(
+                    multiphase::Number(1, 0, 0)
+                ))),
+                Some(Expression::Literal(nodes::Literal::Number(
+                    multiphase::Number(2, 0, 0)
+                ))),
+                None,
+            ],
+        );
+    }
+
+    #[test]
+    fn duplicate_enum_discriminants() {
+        // `Green` is auto-assigned 1, the same as `Blue`'s explicit one.
+        match test_parser("enum Colour(Red: 0, Green, Blue: 1)").parse_enum_definition() {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(_),
+                ..
+            })) => {}
+            other => panic!("expected a described parser error but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn postfix_bind() {
+        let bound = |expression| {
+            Expression::Operator(Operator::Postfix(
+                Box::new(expression),
+                multiphase::PostfixOperator::Bind,
+            ))
+        };
+        let a = || Expression::Symbol(relative(&["a"]));
+
+        assert_eq!(test_parser("a?").parse_expression().unwrap(), bound(a()));
+        assert_eq!(
+            test_parser("a?.b?").parse_expression().unwrap(),
+            bound(Expression::MemberAccess(MemberAccess {
+                target: Box::new(bound(a())),
+                member: Identifier::from("b"),
+            })),
+        );
+        match test_parser("a?()").parse_expression().unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(call)) => {
+                assert_eq!(*call.target, bound(a()));
+            }
+            other => panic!("expected a call but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn type_narrowing_cases() {
+        let mut parser = test_parser("switch animal { Dog as d { } Cat as c { } }");
+        let switch = match parser.parse_switch().unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch)) => switch,
+            other => panic!("expected a switch but got {:?}", other),
+        };
+
+        let matches = switch
+            .cases
+            .into_iter()
+            .flat_map(|case| case.matches)
+            .map(|case_match| (case_match.pattern.item, case_match.narrowed_type))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                (
+                    PatternItem::Identifier(Identifier::from("d")),
+                    Some(TypeReference::new(relative(&["Dog"]))),
+                ),
+                (
+                    PatternItem::Identifier(Identifier::from("c")),
+                    Some(TypeReference::new(relative(&["Cat"]))),
+                ),
+            ],
+        );
+    }
+
+    fn call(target: Expression, arguments: Vec<Expression>) -> Expression {
+        Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(
+            nodes::ExpressionCall {
+                target: Box::new(target),
+                arguments: CallArguments {
+                    type_arguments: vec![],
+                    arguments: arguments
+                        .into_iter()
+                        .map(|value| ValueArgument {
+                            label: ArgumentLabel::Positional,
+                            value,
+                        })
+                        .collect(),
+                },
+            },
+        ))
+    }
+
+    #[test]
+    fn pipes() {
+        let symbol = |name| Expression::Symbol(relative(&[name]));
+
+        assert_eq!(
+            test_parser("a |> f").parse_expression().unwrap(),
+            call(symbol("f"), vec![symbol("a")]),
+        );
+        assert_eq!(
+            test_parser("a |> f(b) |> g").parse_expression().unwrap(),
+            call(
+                symbol("g"),
+                vec![Expression::BranchingAndJumping(
+                    nodes::BranchingAndJumping::Call(nodes::Call {
+                        target: relative(&["f"]),
+                        arguments: CallArguments {
+                            type_arguments: vec![],
+                            arguments: vec![
+                                ValueArgument {
+                                    label: ArgumentLabel::Positional,
+                                    value: symbol("a"),
+                                },
+                                ValueArgument {
+                                    label: ArgumentLabel::Positional,
+                                    value: symbol("b"),
+                                },
+                            ],
+                        },
+                    })
+                )],
+            ),
+        );
+    }
+
+    #[test]
+    fn composition() {
+        assert_eq!(
+            test_parser("f ~ g").parse_expression().unwrap(),
+            Expression::Operator(Operator::Compose(
+                Box::new(Expression::Symbol(relative(&["f"]))),
+                Box::new(Expression::Symbol(relative(&["g"]))),
+            )),
+        );
+    }
+
+    #[test]
+    fn multiple_for_bindings() {
+        for source in &[
+            "for var a = xs, b = ys { }",
+            "for var a = xs, var b = ys { }",
+        ] {
+            let for_loop = test_parser(source).parse_for().unwrap();
+
+            let bindings = for_loop
+                .bindings
+                .into_iter()
+                .map(|binding| (binding.pattern.item, *binding.value.unwrap()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                bindings,
+                vec![
+                    (
+                        PatternItem::Identifier(Identifier::from("a")),
+                        Expression::Symbol(relative(&["xs"])),
+                    ),
+                    (
+                        PatternItem::Identifier(Identifier::from("b")),
+                        Expression::Symbol(relative(&["ys"])),
+                    ),
+                ],
+            );
+        }
+    }
+
+    fn generic(name: &'static str, arguments: Vec<TypeReference>) -> TypeReference {
+        TypeReference {
+            type_arguments: arguments
+                .into_iter()
+                .map(|reference| TypeArgument {
+                    label: ArgumentLabel::Positional,
+                    value: TypeArgumentValue::Given(reference),
+                })
+                .collect(),
+            ..TypeReference::new(relative(&[name]))
+        }
+    }
+
+    #[test]
+    fn generic_type_references() {
+        let int = || TypeReference::new(relative(&["Int"]));
+
+        assert_eq!(
+            test_parser("List[Int]").parse_type_reference().unwrap(),
+            generic("List", vec![int()]),
+        );
+        assert_eq!(
+            test_parser("Map[String, List[Int]]")
+                .parse_type_reference()
+                .unwrap(),
+            generic(
+                "Map",
+                vec![
+                    TypeReference::new(relative(&["String"])),
+                    generic("List", vec![int()]),
+                ],
+            ),
+        );
+    }
+
+    #[test]
+    fn wildcard_type_arguments() {
+        let type_arguments = |source| {
+            test_parser(source)
+                .parse_type_reference()
+                .unwrap()
+                .type_arguments
+                .into_iter()
+                .map(|argument| argument.value)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(type_arguments("List[_]"), vec![TypeArgumentValue::Wildcard]);
+        assert_eq!(
+            type_arguments("Map[String, _]"),
+            vec![
+                TypeArgumentValue::Given(TypeReference::new(relative(&["String"]))),
+                TypeArgumentValue::Wildcard,
+            ],
+        );
+    }
+
+    #[test]
+    fn explicit_and_inferred_call_type_arguments() {
+        let type_arguments = |source| match test_parser(source).parse_expression().unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => call
+                .arguments
+                .type_arguments
+                .into_iter()
+                .map(|argument| argument.value)
+                .collect::<Vec<_>>(),
+            other => panic!("expected a call but got {:?}", other),
+        };
+        let int = || TypeArgumentValue::Given(TypeReference::new(relative(&["Int"])));
+
+        assert_eq!(type_arguments("Foo(x)"), vec![]);
+        assert_eq!(type_arguments("Foo[Int](x)"), vec![int()]);
+        assert_eq!(
+            type_arguments("Foo[Int, _](x)"),
+            vec![int(), TypeArgumentValue::Wildcard],
+        );
+    }
+
+    #[test]
+    fn nested_composite_patterns() {
+        let identifier = |name| Pattern {
+            item: PatternItem::Identifier(Identifier::from(name)),
+            bound_match: None,
+        };
+        let getter = |name, pattern| PatternGetter {
+            name: Identifier::from(name),
+            pattern,
+        };
+        let composite = |name, getters, ignore_rest| Pattern {
+            item: PatternItem::Composite(CompositePattern {
+                r#type: TypeReference::new(relative(&[name])),
+                getters,
+                infer_enum_type: false,
+                ignore_rest,
+            }),
+            bound_match: None,
+        };
+
+        assert_eq!(
+            test_parser("Line(start: Point(x, y: _), end)")
+                .parse_pattern()
+                .unwrap(),
+            composite(
+                "Line",
+                vec![
+                    getter(
+                        "start",
+                        composite(
+                            "Point",
+                            vec![
+                                getter("x", identifier("x")),
+                                getter(
+                                    "y",
+                                    Pattern {
+                                        item: PatternItem::Ignored,
+                                        bound_match: None,
+                                    },
+                                ),
+                            ],
+                            false,
+                        ),
+                    ),
+                    getter("end", identifier("end")),
+                ],
+                false,
+            ),
+        );
+
+        let aliased = Pattern {
+            bound_match: Some(Box::new(identifier("fx"))),
+            ..identifier("first")
+        };
+        for source in &["Point(x: first as fx, ...)", "Point(x: first as fx, ..)"] {
+            assert_eq!(
+                test_parser(source).parse_pattern().unwrap(),
+                composite("Point", vec![getter("x", aliased.clone())], true),
+            );
+        }
+    }
+
+    fn parse_lambda_expression(source: &str) -> Lambda {
+        match test_parser(source).parse_expression().unwrap() {
+            Expression::Literal(nodes::Literal::Lambda(lambda)) => lambda,
+            other => panic!("expected a lambda but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn matching_lambda() {
+        let lambda = parse_lambda_expression("-> { Some(x) { x } None { 0 } }");
+        assert!(lambda.signature.value_parameters.is_empty());
+
+        let switch = match lambda.block.tail.map(|tail| *tail) {
+            Some(Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(switch))) => {
+                switch
+            }
+            other => panic!("expected a switch but got {:?}", other),
+        };
+        assert_eq!(
+            *switch.expression,
+            Expression::Symbol(Symbol::Pseudo(PseudoIdentifier::It)),
+        );
+
+        let patterns = switch
+            .cases
+            .iter()
+            .map(|case| case.matches[0].pattern.item.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            patterns,
+            vec![
+                PatternItem::Composite(CompositePattern {
+                    r#type: TypeReference::new(relative(&["Some"])),
+                    getters: vec![PatternGetter {
+                        name: Identifier::from("x"),
+                        pattern: Pattern {
+                            item: PatternItem::Identifier(Identifier::from("x")),
+                            bound_match: None,
+                        },
+                    }],
+                    infer_enum_type: false,
+                    ignore_rest: false,
+                }),
+                PatternItem::Identifier(Identifier::from("None")),
+            ],
+        );
+        assert_eq!(
+            switch.cases[1].body.tail,
+            Some(Box::new(Expression::Literal(nodes::Literal::Number(
+                multiphase::Number(0, 0, 0)
+            )))),
+        );
+    }
+
+    #[test]
+    fn expression_bodied_lambdas() {
+        let with_parameter = parse_lambda_expression("-> x { x }");
+        assert_eq!(with_parameter.signature.value_parameters.len(), 1);
+        assert_eq!(
+            with_parameter.block.tail,
+            Some(Box::new(Expression::Symbol(relative(&["x"])))),
+        );
+
+        let without_parameters = parse_lambda_expression("-> { foo bar }");
+        assert_eq!(
+            without_parameters.block.expressions,
+            vec![Expression::Symbol(relative(&["foo"]))],
+        );
+        assert_eq!(
+            without_parameters.block.tail,
+            Some(Box::new(Expression::Symbol(relative(&["bar"])))),
+        );
+    }
+
+    #[test]
+    fn lambda_result_types() {
+        let inferred = parse_lambda_expression("-> (x) { x }");
+        assert_eq!(inferred.signature.value_parameters.len(), 1);
+        assert_eq!(
+            inferred.signature.value_parameters[0].explicit_type_annotation,
+            None,
+        );
+        assert_eq!(inferred.signature.explicit_return_type_annotation, None);
+
+        let explicit = parse_lambda_expression("-> (x Int) Int { x }");
+        let int = Some(test_parser("Int").parse_type_reference().unwrap());
+        assert_eq!(
+            explicit.signature.value_parameters[0].explicit_type_annotation,
+            int,
+        );
+        assert_eq!(
+            explicit.signature.explicit_return_type_annotation,
+            int.map(Box::new),
+        );
+        assert_eq!(
+            explicit.block.tail,
+            Some(Box::new(Expression::Symbol(relative(&["x"])))),
+        );
+    }
+
+    #[test]
+    fn zero_parameter_lambdas_and_calls() {
+        let one = Some(Box::new(Expression::Literal(nodes::Literal::Number(
+            multiphase::Number(1, 0, 0),
+        ))));
+
+        for source in &["-> { 1 }", "-> () { 1 }", "-> () Int { 1 }"] {
+            let lambda = parse_lambda_expression(source);
+            assert!(lambda.signature.value_parameters.is_empty(), "{}", source);
+            assert_eq!(lambda.block.tail, one, "{}", source);
+        }
+
+        assert!(call_arguments("f()").is_empty());
+    }
+
+    #[test]
+    fn generic_lambdas() {
+        let generic = parse_lambda_expression("-> [T] (x T) { x }");
+        let type_parameters = &generic.signature.type_parameters;
+        assert_eq!(type_parameters.len(), 1);
+        assert_eq!(type_parameters[0].name, Identifier::from("T"));
+        assert_eq!(
+            generic.signature.value_parameters[0].explicit_type_annotation,
+            Some(test_parser("T").parse_type_reference().unwrap()),
+        );
+
+        let non_generic = parse_lambda_expression("-> (x) { x }");
+        assert!(non_generic.signature.type_parameters.is_empty());
+        assert_eq!(non_generic.signature.value_parameters.len(), 1);
+    }
+
+    #[test]
+    fn reserved_keywords_as_identifiers() {
+        let message = "`unsafe` is reserved for future use and cannot be used as an identifier";
+
+        assert_described_error(
+            test_parser("var unsafe = 1").parse_local_var_binding(),
+            message,
+        );
+        assert_described_error(test_parser("unsafe + 1").parse_expression(), message);
+        assert_described_error(test_parser("fun unsafe() { }").parse_fun(), message);
+    }
+
+    #[test]
+    fn global_symbols() {
+        let symbol = test_parser("global.sylan.lang.Int").parse_symbol().unwrap();
+        assert_eq!(
+            symbol,
+            Symbol::Absolute(SymbolLookup(vec![
+                Identifier::from("sylan"),
+                Identifier::from("lang"),
+                Identifier::from("Int"),
+            ])),
+        );
+
+        assert_described_error(
+            test_parser("global.this").parse_symbol(),
+            "`global.` must be followed by a package name",
+        );
+    }
+
+    #[test]
+    fn expression_bodied_funs() {
+        let braced = test_parser("fun double(x Int) Int { x * 2 }")
+            .parse_fun()
+            .unwrap();
+        let assigned = test_parser("fun double(x Int) Int = x * 2")
+            .parse_fun()
+            .unwrap();
+
+        assert_eq!(assigned.signature, braced.signature);
+        assert!(assigned.block.expressions.is_empty());
+        assert!(assigned.block.bindings.is_empty());
+        assert_eq!(assigned.block.tail, braced.block.tail);
+        assert!(assigned.block.tail.is_some());
+
+        let void = test_parser("fun greet() = print(\"hi\")")
+            .parse_fun()
+            .unwrap();
+        assert_eq!(void.signature.return_type, None);
+        assert!(void.block.tail.is_some());
+
+        // Being after an `=`, the body can start with a parenthesised
+        // subexpression.
+        let grouped = test_parser("fun f() = (1 + 2) * 3").parse_fun().unwrap();
+        assert_eq!(
+            grouped.block.tail.map(|tail| *tail),
+            Some(test_parser("(1 + 2) * 3").parse_expression().unwrap()),
+        );
+    }
+
+    #[test]
+    fn tuples() {
+        let number = |n| Expression::Literal(nodes::Literal::Number(multiphase::Number(n, 0, 0)));
+        let identifier = |name| Pattern {
+            item: PatternItem::Identifier(Identifier::from(name)),
+            bound_match: None,
+        };
+
+        assert_eq!(
+            test_parser("(1, 2)").parse_expression().unwrap(),
+            Expression::Tuple(vec![number(1), number(2)]),
+        );
+        assert_eq!(test_parser("(1)").parse_expression().unwrap(), number(1));
+        assert_eq!(
+            test_parser("(1, 2,)").parse_expression().unwrap(),
+            Expression::Tuple(vec![number(1), number(2)]),
+        );
+        assert_described_error(
+            test_parser("(1,)").parse_expression(),
+            "at least two elements",
+        );
+
+        assert_eq!(
+            test_parser("(x, y)").parse_pattern().unwrap(),
+            Pattern {
+                item: PatternItem::Tuple(vec![identifier("x"), identifier("y")]),
+                bound_match: None,
+            },
+        );
+        assert_described_error(test_parser("(x,)").parse_pattern(), "at least two elements");
+    }
+
+    #[test]
+    fn interpolated_strings() {
+        let parts = |source| match test_parser(source).parse_expression().unwrap() {
+            Expression::Literal(nodes::Literal::InterpolatedString(parts)) => parts,
+            other => panic!("expected an interpolated string but got {:?}", other),
+        };
+        let fragment = |text: &str| InterpolatedStringPart::Fragment(text.to_owned());
+        let interpolation = |name| {
+            InterpolatedStringPart::Interpolation(Box::new(Expression::Symbol(relative(&[name]))))
+        };
+
+        assert_eq!(
+            parts("$\"a{x}b\""),
+            vec![fragment("a"), interpolation("x"), fragment("b")],
+        );
+        assert_eq!(
+            parts("$\"{x}{y} z\""),
+            vec![interpolation("x"), interpolation("y"), fragment(" z")],
+        );
+        assert_eq!(parts("$\"plain\""), vec![fragment("plain")]);
+    }
+
+    #[test]
+    fn list_literals() {
+        let number = |n| Expression::Literal(nodes::Literal::Number(multiphase::Number(n, 0, 0)));
+        let list = |source| test_parser(source).parse_expression().unwrap();
+
+        assert_eq!(list("[]"), Expression::ListLiteral(vec![]));
+        assert_eq!(list("[1]"), Expression::ListLiteral(vec![number(1)]));
+        assert_eq!(
+            list("[1, 2, 3,]"),
+            Expression::ListLiteral(vec![number(1), number(2), number(3)]),
+        );
+
+        match list("foo[T](x)") {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => {
+                assert_eq!(call.arguments.type_arguments.len(), 1)
+            }
+            other => panic!("expected a call but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn block_hashing_ignores_ancestors() {
+        let hash = |block: &Block| {
+            let mut hasher = DefaultHasher::new();
+            block.hash(&mut hasher);
+            hasher.finish()
+        };
+        let tail = || Some(Box::new(Expression::Symbol(relative(&["x"]))));
+
+        let mut innermost = Block::new_root();
+        for _ in 0..1_000 {
+            innermost = Block::within(&Rc::new(innermost));
+        }
+        innermost.tail = tail();
+
+        let orphan = Block {
+            tail: tail(),
+            ..Block::new_root()
+        };
+
+        assert_eq!(hash(&innermost), hash(&orphan));
+        assert_eq!(innermost, orphan);
+    }
+
+    #[test]
+    fn bare_continue() {
+        let mut parser = test_parser("continue");
+        assert_eq!(
+            parser.parse_expression().unwrap(),
+            Expression::Continue(nodes::Continue {
+                label: None,
+                arguments: vec![],
+            }),
+        );
+    }
+
+    #[test]
+    fn labelled_continue() {
+        let mut parser = test_parser("continue outer(x)");
+        assert_eq!(
+            parser.parse_expression().unwrap(),
+            Expression::Continue(nodes::Continue {
+                label: Some(Identifier::from("outer")),
+                arguments: vec![ValueArgument {
+                    label: ArgumentLabel::Positional,
+                    value: Expression::Symbol(relative(&["x"])),
+                }],
+            }),
+        );
+    }
+
+    #[test]
+    fn generator_fun() {
+        let fun = test_parser("gen fun numbers() Int { yield 1 yield 2 }")
+            .parse_fun()
+            .unwrap();
+        assert!(fun.modifiers.is_generator);
+
+        let yielded = |n| {
+            Expression::Yield(nodes::Yield(Box::new(Expression::Literal(
+                nodes::Literal::Number(multiphase::Number(n, 0, 0)),
+            ))))
+        };
+        assert_eq!(fun.block.expressions, vec![yielded(1)]);
+        assert_eq!(fun.block.tail, Some(Box::new(yielded(2))));
+    }
+
+    #[test]
+    fn yield_outside_generator() {
+        let mut parser = test_parser("fun numbers() Int { yield 1 }");
+        assert!(parser.parse_fun().is_err());
+    }
+
+    #[test]
+    fn fun_sydoc() {
+        let before = test_parser("/** Does foo. */ fun foo() { }")
+            .parse_fun()
+            .unwrap();
+        let after_name = test_parser("fun foo /** Does foo. */ () { }")
+            .parse_fun()
+            .unwrap();
+
+        assert_eq!(before.signature.sydoc, Some(SyDoc::from(" Does foo. ")));
+        assert_eq!(before.signature, after_name.signature);
+    }
+
+    #[test]
+    fn parameter_sydocs() {
+        let fun = test_parser(
+            "fun convert[/** The source. */ From, /** The target. */ To](/** Converted. */ x From) To { }",
+        )
+        .parse_fun()
+        .unwrap();
+        let class = test_parser("class Box[T, /** What it holds. */ U](x U) { }")
+            .parse_class_definition()
+            .unwrap();
+
+        let type_sydocs = |parameters: &[TypeParameter]| {
+            parameters
+                .iter()
+                .map(|parameter| parameter.sydoc.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            type_sydocs(&fun.signature.type_parameters),
+            vec![
+                Some(SyDoc::from(" The source. ")),
+                Some(SyDoc::from(" The target. ")),
+            ],
+        );
+        assert_eq!(
+            fun.signature.value_parameters[0].sydoc,
+            Some(SyDoc::from(" Converted. ")),
+        );
+        assert_eq!(
+            type_sydocs(&class.type_parameters),
+            vec![None, Some(SyDoc::from(" What it holds. "))],
+        );
+
+        assert_described_error(
+            test_parser("[/** Before. */ T /** After. */]").parse_type_parameter_list(),
+            "a parameter can only have one SyDoc",
+        );
+    }
+
+    #[test]
+    fn method_sydoc() {
+        let mut parser = test_parser("class Foo { /** Does bar. */ fun bar() { } }");
+        let class = match parser.parse_class_definition().unwrap().item {
+            nodes::TypeItem::Class(class) => class,
+            other => panic!("expected a class but got {:?}", other),
+        };
+
+        assert_eq!(
+            class.methods[0].r#abstract.signature.sydoc,
+            Some(SyDoc::from(" Does bar. ")),
+        );
+    }
+
+    #[test]
+    fn member_sydocs() {
+        let mut parser = test_parser(
+            "
+            class Foo {
+                /** Counts things. */
+                var count = 0
+                var undocumented = 1
+                /** Does bar. */
+                fun bar() { }
+            }
+            ",
+        );
+        let class = match parser.parse_class_definition().unwrap().item {
+            nodes::TypeItem::Class(class) => class,
+            other => panic!("expected a class but got {:?}", other),
+        };
+        let field_sydocs = class
+            .fields
+            .into_iter()
+            .map(|field| field.sydoc)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            field_sydocs,
+            vec![Some(SyDoc::from(" Counts things. ")), None],
+        );
+        assert_eq!(
+            class.methods[0].r#abstract.signature.sydoc,
+            Some(SyDoc::from(" Does bar. ")),
+        );
+
+        assert_described_error(
+            test_parser("class Foo { /** Dangling. */ 1 }").parse_class_definition(),
+            "must come before a field or a method",
+        );
+    }
+
+    #[test]
+    fn fun_documented_twice() {
+        let mut parser = test_parser("/** Does foo. */ fun foo /** Does foo. */ () { }");
+        assert!(parser.parse_fun().is_err());
+    }
+
+    #[test]
+    fn break_to_outer_label() {
+        let mut parser =
+            test_parser("for outer var i = 0 { for inner var j = 0 { break outer i } }");
+        let outer = parser.parse_for().unwrap();
+        assert_eq!(outer.reiteration_symbol, Some(Identifier::from("outer")));
+
+        let inner = match outer.scope.tail.map(|tail| *tail) {
+            Some(Expression::BranchingAndJumping(nodes::BranchingAndJumping::For(inner))) => inner,
+            other => panic!("expected an inner for loop but got {:?}", other),
+        };
+        assert_eq!(
+            inner.scope.tail,
+            Some(Box::new(Expression::Break(nodes::Break {
+                label: Some(Identifier::from("outer")),
+                value: Some(Box::new(Expression::Symbol(relative(&["i"])))),
+            }))),
+        );
+    }
+
+    #[test]
+    fn break_with_optional_label_and_value() {
+        let break_in_loop = |body| {
+            let source = format!("for outer var i = 0 {{ {} }}", body);
+            match test_parser(&source)
+                .parse_for()
+                .unwrap()
+                .scope
+                .tail
+                .map(|tail| *tail)
+            {
+                Some(Expression::Break(break_expression)) => break_expression,
+                other => panic!("expected a break but got {:?}", other),
+            }
+        };
+        let x = || Some(Box::new(Expression::Symbol(relative(&["x"]))));
+
+        assert_eq!(
+            break_in_loop("break"),
+            nodes::Break {
+                label: None,
+                value: None,
+            },
+        );
+        assert_eq!(
+            break_in_loop("break x"),
+            nodes::Break {
+                label: None,
+                value: x(),
+            },
+        );
+        assert_eq!(
+            break_in_loop("break outer x"),
+            nodes::Break {
+                label: Some(Identifier::from("outer")),
+                value: x(),
+            },
+        );
+        assert_eq!(
+            break_in_loop("break outer"),
+            nodes::Break {
+                label: Some(Identifier::from("outer")),
+                value: None,
+            },
+        );
+
+        let value = |body| break_in_loop(body).value.map(|value| *value);
+        assert!(matches!(
+            value("break x + 1"),
+            Some(Expression::Operator(Operator::OverloadableInfix(..))),
+        ));
+        assert_eq!(
+            value("break f(1)"),
+            Some(Expression::BranchingAndJumping(
+                nodes::BranchingAndJumping::Call(nodes::Call {
+                    target: relative(&["f"]),
+                    arguments: CallArguments {
+                        type_arguments: vec![],
+                        arguments: vec![ValueArgument {
+                            label: ArgumentLabel::Positional,
+                            value: Expression::Literal(nodes::Literal::Number(multiphase::Number(
+                                1, 0, 0
+                            ),)),
+                        }],
+                    },
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn break_to_unknown_label() {
+        let mut parser = test_parser("for outer var i = 0 { break inner i }");
+        assert!(parser.parse_for().is_err());
+    }
+
+    fn parse_slice_fragments(source: &str) -> Vec<nodes::SliceFragment> {
+        test_parser(source).parse_slice().unwrap().0
+    }
+
+    #[test]
+    fn indexing_versus_type_arguments() {
+        let array = || Box::new(Expression::Symbol(relative(&["arr"])));
+
+        assert_eq!(
+            test_parser("arr[0]").parse_expression().unwrap(),
+            Expression::Operator(Operator::Index(
+                array(),
+                Box::new(Expression::Literal(nodes::Literal::Number(
+                    multiphase::Number(0, 0, 0)
+                ))),
+            )),
+        );
+
+        match test_parser("f[T](x)").parse_expression().unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => {
+                assert_eq!(call.target, relative(&["f"]));
+                assert_eq!(call.arguments.type_arguments.len(), 1);
+                assert_eq!(call.arguments.arguments.len(), 1);
+            }
+            other => panic!("expected a call but got {:?}", other),
+        }
+
+        match test_parser("arr[|1 : 2|]").parse_expression().unwrap() {
+            Expression::Operator(Operator::MultiSlice(target, slice)) => {
+                assert_eq!(target, array());
+                assert_eq!(slice.0.len(), 1);
+            }
+            other => panic!("expected a slice but got {:?}", other),
+        }
+
+        match test_parser("rows()[i][j]").parse_expression().unwrap() {
+            Expression::Operator(Operator::Index(target, _)) => {
+                assert!(matches!(*target, Expression::Operator(Operator::Index(..))));
+            }
+            other => panic!("expected an index but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn slices_with_expression_bounds() {
+        let symbol = |name| Expression::Symbol(relative(&[name]));
+
+        assert_eq!(
+            parse_slice_fragments("[|start : end|]"),
+            vec![nodes::SliceFragment::Slice(Box::new(nodes::Slice {
+                start: Some(symbol("start")),
+                step: None,
+                end: Some(symbol("end")),
+            }))],
+        );
+        assert_eq!(
+            parse_slice_fragments("[|start : step * 2 : end|]"),
+            vec![nodes::SliceFragment::Slice(Box::new(nodes::Slice {
+                start: Some(symbol("start")),
+                step: Some(Expression::Operator(Operator::OverloadableInfix(
+                    Box::new(symbol("step")),
+                    OverloadableInfixOperator::Multiply,
+                    Box::new(Expression::Literal(nodes::Literal::Number(
+                        multiphase::Number(2, 0, 0),
+                    ))),
+                ))),
+                end: Some(symbol("end")),
+            }))],
+        );
+        assert_eq!(
+            parse_slice_fragments("[|: end, ...|]"),
+            vec![
+                nodes::SliceFragment::Slice(Box::new(nodes::Slice {
+                    start: None,
+                    step: None,
+                    end: Some(symbol("end")),
+                })),
+                nodes::SliceFragment::Ellipsis,
+            ],
+        );
+    }
+
+    #[test]
+    fn labelled_with() {
+        let context = |source| match test_parser(source).parse_expression().unwrap() {
+            Expression::Context(context) => context,
+            other => panic!("expected a with block but got {:?}", other),
+        };
+
+        let labelled = context("with setup { break setup 1 }");
+        assert_eq!(labelled.label, Some(Identifier::from("setup")));
+        assert_eq!(
+            labelled.scope.tail,
+            Some(Box::new(Expression::Break(nodes::Break {
+                label: Some(Identifier::from("setup")),
+                value: Some(Box::new(Expression::Literal(nodes::Literal::Number(
+                    multiphase::Number(1, 0, 0)
+                )))),
+            }))),
+        );
+
+        let unlabelled = context("with { 1 }");
+        assert_eq!(unlabelled.label, None);
+        assert!(unlabelled.scope.tail.is_some());
+    }
+
+    #[test]
+    fn explicit_accessibility() {
+        let package = test_parser("package public foo { }")
+            .parse_package_definition()
+            .unwrap();
+        let fun = test_parser("fun internal foo() { }").parse_fun().unwrap();
+        let binding = test_parser("final public foo = 1").parse_binding().unwrap();
+
+        assert_eq!(package.accessibility, Accessibility::Public);
+        assert_eq!(fun.modifiers.accessibility, Accessibility::Internal);
+        assert_eq!(binding.accessibility, Accessibility::Public);
+    }
+
+    #[test]
+    fn internal_top_level_items() {
+        let main = test_parser("package main\npackage internal foo { }\nfinal internal bar = 1")
+            .parse_main_package()
+            .unwrap();
+
+        assert_eq!(main.package.accessibility, Accessibility::Public);
+        match &main.package.items[..] {
+            [Item::Package(package), Item::Final(binding)] => {
+                assert_eq!(package.name, Identifier::from("foo"));
+                assert_eq!(package.accessibility, Accessibility::Internal);
+                assert_eq!(binding.accessibility, Accessibility::Internal);
+            }
+            items => panic!("expected a package and a binding, got {:?}", items),
+        }
+    }
+
+    #[test]
+    fn annotations() {
+        let main = test_parser(
+            "package main\n@inline fun f() { }\n@route(\"/path\") @deprecated fun g() { }",
+        )
+        .parse_main_package()
+        .unwrap();
+
+        let annotated = |item: &Item| match item {
+            Item::Annotated(nodes::Annotated { annotations, item }) => {
+                let name = match &**item {
+                    Item::Fun(fun) => fun.signature.name.clone(),
+                    other => panic!("expected a fun but got {:?}", other),
+                };
+                (annotations.clone(), name)
+            }
+            other => panic!("expected an annotated item but got {:?}", other),
+        };
+
+        let (annotations, name) = annotated(&main.package.items[0]);
+        assert_eq!(name, Identifier::from("f"));
+        assert_eq!(
+            annotations,
+            vec![nodes::Annotation {
+                symbol: relative(&["inline"]),
+                arguments: vec![],
+            }],
+        );
+
+        let (annotations, name) = annotated(&main.package.items[1]);
+        assert_eq!(name, Identifier::from("g"));
+        assert_eq!(annotations.len(), 2);
+        assert_eq!(annotations[0].symbol, relative(&["route"]));
+        assert_eq!(
+            annotations[0].arguments[0].value,
+            Expression::Literal(nodes::Literal::String(multiphase::SylanString::from(
+                "/path"
+            ))),
+        );
+        assert_eq!(annotations[1].symbol, relative(&["deprecated"]));
+
+        assert_described_error(
+            test_parser("@inline 1").parse_annotated_item(),
+            "annotations must be followed by the item they annotate",
+        );
+    }
+
+    #[test]
+    fn primitive_types() {
+        let int8 = test_parser("Int8").parse_type_reference().unwrap();
+        let long = test_parser("Long").parse_type_reference().unwrap();
+        let uint64 = test_parser("UInt64").parse_type_reference().unwrap();
+        let user_type = test_parser("Foo").parse_type_reference().unwrap();
+        let qualified = test_parser("foo.Int8").parse_type_reference().unwrap();
+
+        assert_eq!(int8.primitive, Some(PrimitiveType::signed(8)));
+        assert_eq!(long.primitive, Some(PrimitiveType::signed(64)));
+        assert_eq!(uint64.primitive, Some(PrimitiveType::unsigned(64)));
+        assert_eq!(user_type.primitive, None);
+        assert_eq!(qualified.primitive, None);
+    }
+
+    #[test]
+    fn type_parameter_default_values() {
+        let parameters = test_parser("[T: Foo, U]")
+            .parse_type_parameter_list()
+            .unwrap();
+
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(
+            parameters[0].default_value,
+            Some(TypeReference::new(relative(&["Foo"])))
+        );
+        assert_eq!(parameters[1].default_value, None);
+    }
+
+    #[test]
+    fn type_parameter_upper_bounds() {
+        let t = || TypeReference::new(relative(&["T"]));
+        let parameters = test_parser("[T extends Comparable[T], U extends Show & Eq[U], V]")
+            .parse_type_parameter_list()
+            .unwrap();
+        let upper_bounds = parameters
+            .into_iter()
+            .map(|parameter| parameter.upper_bounds)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            upper_bounds,
+            vec![
+                vec![generic("Comparable", vec![t()])],
+                vec![
+                    TypeReference::new(relative(&["Show"])),
+                    generic("Eq", vec![TypeReference::new(relative(&["U"]))]),
+                ],
+                vec![],
+            ],
+        );
+        assert!(test_parser("Comparable[T]")
+            .parse_type_constraints()
+            .is_err());
+    }
+
+    #[test]
+    fn conjoined_type_constraints() {
+        let constraints = test_parser("extends A & B & C")
+            .parse_type_constraints()
+            .unwrap();
+        assert_eq!(
+            constraints,
+            vec![
+                TypeReference::new(relative(&["A"])),
+                TypeReference::new(relative(&["B"])),
+                TypeReference::new(relative(&["C"])),
+            ],
+        );
+
+        let parameters = test_parser("[T extends A & B]")
+            .parse_type_parameter_list()
+            .unwrap();
+        assert_eq!(parameters[0].upper_bounds.len(), 2);
+
+        assert!(test_parser("extends A &").parse_type_constraints().is_err());
+    }
+
+    #[test]
+    fn modifiers() {
+        let whitelist = ModifierSets::default().function;
+        let mut parser = test_parser("public ignorable fun");
+
+        let modifiers = parser.parse_modifiers(&whitelist).unwrap();
+
+        assert_eq!(
+            modifiers,
+            vec![
+                Modifier::Accessibility(Accessibility::Public),
+                Modifier::Ignorable,
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(
+            parser.peek(),
+            Some(Token::DeclarationHead(DeclarationHead::Fun)),
+        );
+    }
+
+    #[test]
+    fn modifier_outside_whitelist_is_left() {
+        let whitelist = ModifierSets::default().function;
+        let mut parser = test_parser("public override");
+
+        parser.parse_modifiers(&whitelist).unwrap();
+
+        assert_eq!(parser.peek(), Some(Token::Modifier(Modifier::Override)));
+    }
+
+    #[test]
+    fn duplicate_modifiers() {
+        let whitelist = ModifierSets::default().function;
+        match test_parser("public public").parse_modifiers(&whitelist) {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(_),
+                position: Some(_),
+            })) => {}
+            other => panic!("expected a positioned parser error but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn quoted_keyword_as_binding_name() {
+        let binding = test_parser("final `class` = 1").parse_binding().unwrap();
+
+        assert_eq!(
+            binding.binding.pattern.item,
+            PatternItem::Identifier(Identifier::from("class")),
+        );
+    }
+
+    #[test]
+    fn quoted_keyword_as_call_target() {
+        match test_parser("`class`(1)").parse_expression().unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => {
+                assert_eq!(call.target, relative(&["class"]));
+            }
+            other => panic!("expected a call but got {:?}", other),
+        }
+    }
+
+    fn labelled(label: &'static str, n: i64) -> ValueArgument {
+        ValueArgument {
+            label: ArgumentLabel::Labelled(Identifier::from(label)),
+            value: Expression::Literal(nodes::Literal::Number(multiphase::Number(n, 0, 0))),
+        }
+    }
+
+    #[test]
+    fn untyped_record_literal() {
+        assert_eq!(
+            test_parser("(x: 1, y: 2)").parse_expression().unwrap(),
+            Expression::RecordLiteral(nodes::RecordLiteral {
+                record_type: None,
+                fields: vec![labelled("x", 1), labelled("y", 2)],
+            }),
+        );
+    }
+
+    #[test]
+    fn typed_record_literal() {
+        assert_eq!(
+            test_parser("Point(x: 1, y: 2)").parse_expression().unwrap(),
+            Expression::RecordLiteral(nodes::RecordLiteral {
+                record_type: Some(TypeReference::new(relative(&["Point"]))),
+                fields: vec![labelled("x", 1), labelled("y", 2)],
+            }),
+        );
+    }
+
+    #[test]
+    fn dotted_package_names() {
+        let package = test_parser("package public a.b.c { final x = 1 }")
+            .parse_package_definition()
+            .unwrap();
+
+        let mut names = vec![];
+        let mut current = &package;
+        loop {
+            names.push(current.name.clone());
+            assert_eq!(current.accessibility, Accessibility::Public);
+            match &current.items[..] {
+                [Item::Package(inner)] => current = inner,
+                [Item::Final(_)] => break,
+                items => panic!("expected a single package or binding, got {:?}", items),
+            }
+        }
+        assert_eq!(
+            names,
+            vec![
+                Identifier::from("a"),
+                Identifier::from("b"),
+                Identifier::from("c"),
+            ],
+        );
+
+        let single = test_parser("package a { }")
+            .parse_package_definition()
+            .unwrap();
+        assert_eq!(single.name, Identifier::from("a"));
+        assert!(single.items.is_empty());
+    }
+
+    #[test]
+    fn extern_final_volatility() {
+        let main = test_parser(
+            "package main\nextern final x Int\nextern nonvolatile final public y Int\nfinal z = 1",
+        )
+        .parse_main_package()
+        .unwrap();
+
+        let finals = main
+            .package
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Final(binding) => binding,
+                other => panic!("expected a final but got {:?}", other),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(finals[0].volatility, Some(Volatility::Volatile));
+        assert_eq!(finals[0].binding.value, None);
+        assert_eq!(
+            finals[0].binding.explicit_type_annotation,
+            Some(test_parser("Int").parse_type_reference().unwrap()),
+        );
+        assert_eq!(finals[1].volatility, Some(Volatility::Nonvolatile));
+        assert_eq!(finals[1].accessibility, Accessibility::Public);
+        assert_eq!(finals[2].volatility, None);
+
+        let volatility = |source| test_parser(source).parse_binding().unwrap().volatility;
+        assert_eq!(
+            volatility("final x Int = extern"),
+            Some(Volatility::Volatile)
+        );
+        assert_eq!(
+            volatility("final nonvolatile x Int = extern"),
+            Some(Volatility::Nonvolatile),
+        );
+        assert_eq!(
+            volatility("extern final nonvolatile x Int"),
+            Some(Volatility::Nonvolatile),
+        );
+
+        assert_described_error(
+            test_parser("final volatile nonvolatile x Int = extern").parse_binding(),
+            "both volatile and nonvolatile",
+        );
+        assert_described_error(
+            test_parser("final x = extern").parse_binding(),
+            "must state its type",
+        );
+        assert_described_error(
+            test_parser("final volatile x = 1").parse_binding(),
+            "only extern finals",
+        );
+        assert_described_error(
+            test_parser("extern final x Int = 1").parse_binding(),
+            "cannot have a value",
+        );
+        assert_described_error(
+            test_parser("extern nonvolatile final nonvolatile x Int").parse_binding(),
+            "listed twice",
+        );
+    }
+
+    #[test]
+    fn default_accessibility() {
+        let package = test_parser("package foo { }")
+            .parse_package_definition()
+            .unwrap();
+        let fun = test_parser("fun foo() { }").parse_fun().unwrap();
+        let binding = test_parser("final foo = 1").parse_binding().unwrap();
+
+        assert_eq!(package.accessibility, Accessibility::Private);
+        assert_eq!(fun.modifiers.accessibility, Accessibility::Private);
+        assert_eq!(binding.accessibility, Accessibility::Private);
+    }
+
+    #[test]
+    fn this_member_access_inside_method() {
+        let mut parser = test_parser("class Foo { fun bar() Int { this.x } }");
+        let class = match parser.parse_class_definition().unwrap().item {
+            nodes::TypeItem::Class(class) => class,
+            other => panic!("expected a class but got {:?}", other),
+        };
+
+        assert_eq!(
+            class.methods[0].scope.tail,
+            Some(Box::new(Expression::MemberAccess(MemberAccess {
+                target: Box::new(Expression::Symbol(Symbol::Pseudo(PseudoIdentifier::This))),
+                member: Identifier::from("x"),
+            }))),
+        );
+    }
+
+    #[test]
+    fn pseudo_identifier_lookups() {
+        let lookup = |names: &[&'static str]| {
+            SymbolLookup(names.iter().map(|&name| Identifier::from(name)).collect())
+        };
+
+        assert_eq!(
+            test_parser("this.package.config").parse_symbol().unwrap(),
+            Symbol::PseudoLookup(PseudoIdentifier::ThisPackage, lookup(&["config"])),
+        );
+        assert_eq!(
+            test_parser("this.module.a.b").parse_symbol().unwrap(),
+            Symbol::PseudoLookup(PseudoIdentifier::ThisModule, lookup(&["a", "b"])),
+        );
+        assert_eq!(
+            test_parser("super.field").parse_symbol().unwrap(),
+            Symbol::PseudoLookup(PseudoIdentifier::Super, lookup(&["field"])),
+        );
+        assert_eq!(
+            test_parser("this.package").parse_symbol().unwrap(),
+            Symbol::Pseudo(PseudoIdentifier::ThisPackage),
+        );
+        assert!(test_parser("this.package.").parse_symbol().is_err());
+    }
+
+    #[test]
+    fn this_outside_type_body() {
+        let mut parser = test_parser("this.x");
+        assert!(parser.parse_expression().is_err());
+    }
+
+    fn call_arguments(source: &str) -> Vec<ValueArgument> {
+        match test_parser(source).parse_expression().unwrap() {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(call)) => {
+                call.arguments.arguments
+            }
+            other => panic!("expected a call but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn member_handles() {
+        let to_string = Expression::MemberHandle(relative(&["toString"]));
+
+        assert_eq!(
+            test_parser(":toString").parse_expression().unwrap(),
+            to_string,
+        );
+
+        let arguments = call_arguments("list.map(:toString)");
+        assert_eq!(arguments.len(), 1);
+        assert_eq!(arguments[0].label, ArgumentLabel::Positional);
+        assert_eq!(arguments[0].value, to_string);
+    }
+
+    #[test]
+    fn explicitly_unlabelled_arguments() {
+        let argument = |label| ValueArgument {
+            label,
+            value: Expression::Symbol(relative(&["x"])),
+        };
+
+        assert_eq!(
+            call_arguments("f(_: x)"),
+            vec![argument(ArgumentLabel::Unlabelled)],
+        );
+        assert_eq!(
+            call_arguments("f(label: x)"),
+            vec![argument(ArgumentLabel::Labelled(Identifier::from("label")))],
+        );
+        assert_eq!(
+            call_arguments("f(x)"),
+            vec![argument(ArgumentLabel::Positional)],
+        );
+        assert_eq!(
+            call_arguments("f(_: x, x)"),
+            vec![
+                argument(ArgumentLabel::Unlabelled),
+                argument(ArgumentLabel::Positional),
+            ],
+        );
+    }
+
+    #[test]
+    fn trailing_lambda() {
+        let arguments = call_arguments("twice(3) { it * 2 }");
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(arguments[1].label, ArgumentLabel::Positional);
+        assert!(matches!(
+            arguments[1].value,
+            Expression::Literal(nodes::Literal::Lambda(..)),
+        ));
+    }
+
+    #[test]
+    fn trailing_blocks() {
+        let lambda = |argument: &ValueArgument| match &argument.value {
+            Expression::Literal(nodes::Literal::Lambda(lambda)) => lambda.clone(),
+            other => panic!("expected a lambda but got {:?}", other),
+        };
+
+        let arguments = call_arguments("xs.map { x -> x }");
+        assert_eq!(arguments.len(), 1);
+        assert_eq!(arguments[0].label, ArgumentLabel::Positional);
+        let mapper = lambda(&arguments[0]);
+        assert_eq!(mapper.signature.value_parameters.len(), 1);
+        assert_eq!(
+            mapper.block.tail,
+            Some(Box::new(Expression::Symbol(relative(&["x"])))),
+        );
+
+        let arguments = call_arguments("xs.fold(0) { sum, x -> sum + x }");
+        assert_eq!(arguments.len(), 2);
+        assert_eq!(lambda(&arguments[1]).signature.value_parameters.len(), 2);
+
+        let arguments = call_arguments("xs.forEach { print(it) }");
+        assert!(lambda(&arguments[0]).signature.value_parameters.is_empty());
+
+        // Calls without a trailing block are unaffected.
+        let arguments = call_arguments("xs.map(f)");
+        assert_eq!(arguments.len(), 1);
+        assert_eq!(arguments[0].value, Expression::Symbol(relative(&["f"])));
+        assert_eq!(
+            test_parser("xs.map").parse_expression().unwrap(),
+            Expression::Symbol(relative(&["xs", "map"])),
+        );
+
+        // Member accesses on other expressions take them too.
+        match test_parser("5.times { send(CounterMsg.Increment, to: counter) }")
+            .parse_expression()
+            .unwrap()
+        {
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::ExpressionCall(call)) => {
+                assert!(matches!(*call.target, Expression::MemberAccess(..)));
+                assert_eq!(call.arguments.arguments.len(), 1);
+                lambda(&call.arguments.arguments[0]);
+            }
+            other => panic!("expected a call but got {:?}", other),
+        }
+
+        // A `{` after an `if`'s condition or a `switch`'s subject starts its
+        // own block, unless the trailing block is within brackets.
+        for source in &["if xs.isEmpty { 0 }", "if (xs.any { it }) { 0 }"] {
+            assert!(matches!(
+                test_parser(source).parse_expression().unwrap(),
+                Expression::BranchingAndJumping(nodes::BranchingAndJumping::If(..)),
+            ));
+        }
+        assert!(matches!(
+            test_parser("switch x { y { 0 } }")
+                .parse_expression()
+                .unwrap(),
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Switch(..)),
+        ));
+    }
+
+    #[test]
+    fn trailing_lambda_or_cascade() {
+        for source in &[
+            "a -> b",
+            "xs.map -> x",
+            "a.b -> c",
+            "f(x) -> b",
+            "f(x) -> b.c(1)",
+            "(a) -> b",
+            "(f(x)) -> b",
+        ] {
+            assert!(matches!(
+                test_parser(source).parse_expression().unwrap(),
+                Expression::Operator(Operator::OverloadableInfix(
+                    _,
+                    OverloadableInfixOperator::Cascade,
+                    _,
+                )),
+            ));
+        }
+
+        // Errors within a trailing block are still reported as they are.
+        assert_described_error(
+            test_parser("f(x) { timeout }").parse_expression(),
+            "case of a `select`",
+        );
+    }
+
+    #[test]
+    fn labelled_trailing_lambda() {
+        for source in &[
+            "fetch(url) completion: { it }",
+            "fetch(url) completion: { result -> result }",
+            "fetch(url) completion: -> { it }",
+        ] {
+            let arguments = call_arguments(source);
+            assert_eq!(arguments.len(), 2);
+            assert_eq!(
+                arguments[1].label,
+                ArgumentLabel::Labelled(Identifier::from("completion")),
+            );
+            assert!(matches!(
+                arguments[1].value,
+                Expression::Literal(nodes::Literal::Lambda(..)),
+            ));
+        }
+    }
+
+    #[test]
+    fn predicates_borrow_tokens() {
+        let mut parser = test_parser("a b c");
+        assert!(parser.match_next(|token| matches!(token, Token::Identifier(..))));
+        assert!(parser.match_nth(2, |token| *token
+            == Token::Identifier(Identifier::from("c"))));
+        assert!(!parser.match_nth(1, |token| *token
+            == Token::Identifier(Identifier::from("c"))));
+    }
+
+    #[test]
+    fn large_token_stream() {
+        let count = 10_000;
+        let source = format!("f({})", vec!["x"; count].join(", "));
+        assert_eq!(call_arguments(&source).len(), count);
+    }
+
+    fn parse_class_methods(source: &str) -> Vec<ConcreteMethod> {
+        match test_parser(source).parse_class_definition().unwrap().item {
+            nodes::TypeItem::Class(class) => class.methods,
+            other => panic!("expected a class but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn overriding_methods() {
+        let methods = parse_class_methods(
+            "
+            class Point implements Show {
+                fun public override toString() String { \"\" }
+                fun public override missing(x Int, to y Int) { }
+                fun public plain() { }
+            }
+            ",
+        );
+        let overrides = methods
+            .into_iter()
+            .map(|method| method.r#abstract.modifiers.overrides)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            overrides,
+            vec![
+                Some(OverrideTarget {
+                    name: Identifier::from("toString"),
+                    labels: vec![],
+                }),
+                // Whether `Show` declares it is only known once interfaces are
+                // resolved, so it is recorded all the same.
+                Some(OverrideTarget {
+                    name: Identifier::from("missing"),
+                    labels: vec![Some(Identifier::from("x")), Some(Identifier::from("to"))],
+                }),
+                None,
+            ],
+        );
+    }
+
+    #[test]
+    fn throw_within_infix_operators() {
+        let symbol = |name| Box::new(Expression::Symbol(relative(&[name])));
+        let throw = |thrown| Expression::Throw(nodes::Throw(thrown));
+        let or = |left, right| {
+            Expression::Operator(Operator::OverloadableInfix(
+                left,
+                OverloadableInfixOperator::Or,
+                right,
+            ))
+        };
+
+        assert_eq!(
+            test_parser("a || throw e").parse_expression().unwrap(),
+            or(symbol("a"), Box::new(throw(symbol("e")))),
+        );
+        assert_eq!(
+            test_parser("throw e || b").parse_expression().unwrap(),
+            throw(Box::new(or(symbol("e"), symbol("b")))),
+        );
+        assert_eq!(
+            test_parser("(throw e) || b").parse_expression().unwrap(),
+            or(Box::new(throw(symbol("e"))), symbol("b")),
+        );
+    }
+
+    #[test]
+    fn negation() {
+        let symbol = |name| Box::new(Expression::Symbol(relative(&[name])));
+        let negate = |operand| {
+            Expression::Operator(Operator::Prefix(PrefixOperator::Negate, Box::new(operand)))
+        };
+        let infix = |left, operator, right| {
+            Expression::Operator(Operator::OverloadableInfix(left, operator, right))
+        };
+
+        assert_eq!(
+            test_parser("-x").parse_expression().unwrap(),
+            negate(*symbol("x")),
+        );
+        assert_eq!(
+            test_parser("-(a + b)").parse_expression().unwrap(),
+            negate(infix(
+                symbol("a"),
+                OverloadableInfixOperator::Add,
+                symbol("b"),
+            )),
+        );
+        assert_eq!(
+            test_parser("a - b").parse_expression().unwrap(),
+            infix(
+                symbol("a"),
+                OverloadableInfixOperator::Subtract,
+                symbol("b")
+            ),
+        );
+        assert_eq!(
+            test_parser("a - -b").parse_expression().unwrap(),
+            infix(
+                symbol("a"),
+                OverloadableInfixOperator::Subtract,
+                Box::new(negate(*symbol("b"))),
+            ),
+        );
+        assert_eq!(
+            test_parser("-x * y").parse_expression().unwrap(),
+            infix(
+                Box::new(negate(*symbol("x"))),
+                OverloadableInfixOperator::Multiply,
+                symbol("y"),
+            ),
+        );
+
+        // Negations can also start a statement or a block's tail.
+        let fun = test_parser("fun f(x Int) Int { -x }").parse_fun().unwrap();
+        assert_eq!(fun.block.tail, Some(Box::new(negate(*symbol("x")))));
+    }
+
+    #[test]
+    fn node_at_position() {
+        let source = "package main\nvar x = first + second(1)\nfun f() Int = 2\n";
+        let file = test_parser(source).with_spans().parse_main_file().unwrap();
+        let expression_at = |line, column| match nodes::node_at(&file, Position::at(line, column)) {
+            Some(nodes::Node::Expression(expression)) => Some(expression.clone()),
+            Some(other) => panic!("expected an expression but got {:?}", other),
+            None => None,
+        };
+
+        // Inside an operand, including at its first character.
+        assert_eq!(
+            expression_at(2, 11),
+            Some(Expression::Symbol(relative(&["first"]))),
+        );
+        assert_eq!(
+            expression_at(2, 9),
+            Some(Expression::Symbol(relative(&["first"]))),
+        );
+        assert_eq!(
+            expression_at(2, 24),
+            Some(test_parser("1").parse_expression().unwrap()),
+        );
+
+        // Between tokens, or on one that isn't a node itself.
+        let sum = test_parser("first + second(1)").parse_expression().unwrap();
+        assert_eq!(expression_at(2, 14), Some(sum.clone()));
+        assert_eq!(expression_at(2, 15), Some(sum));
+        assert_eq!(
+            expression_at(2, 23),
+            Some(test_parser("second(1)").parse_expression().unwrap()),
+        );
+
+        // Outside of any expression.
+        assert_eq!(expression_at(1, 3), None);
+        assert_eq!(expression_at(2, 26), None);
+
+        // Items are found too, unless an expression inside them is tighter.
+        assert!(matches!(
+            nodes::node_at(&file, Position::at(3, 2)),
+            Some(nodes::Node::Item(Item::Fun(..))),
+        ));
+        assert_eq!(
+            expression_at(3, 15),
+            Some(test_parser("2").parse_expression().unwrap()),
+        );
+
+        let unrecorded = test_parser(source).parse_main_file().unwrap();
+        assert!(unrecorded.spans.is_empty());
+        assert_eq!(nodes::node_at(&unrecorded, Position::at(2, 11)), None);
+    }
+
+    #[test]
+    fn main_package_name() {
+        let file = test_parser("package main\nvar x = 1")
+            .parse_main_file()
+            .unwrap();
+        assert_eq!(file.package.package.name, Identifier::from("main"));
+
+        match test_parser("package other\nvar x = 1").parse_main_file() {
+            Err(Error::Parser(ParserError {
+                description: ParserErrorDescription::Described(_),
+                position: Some(_),
+            })) => {}
+            other => panic!("expected a misnamed main package but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn recovering_from_a_broken_expression() {
+        let file = test_parser("package main\nvar x = 1\nx + * 2\nvar y = 2\nprint(y)")
+            .parse_recovering()
+            .unwrap();
+        let main = file.package.block;
+
+        assert_eq!(main.bindings.len(), 2);
+        assert_eq!(main.expressions.len(), 2);
+        assert!(matches!(main.expressions[0], Expression::Error(..)));
+        assert_eq!(
+            main.expressions[1],
+            Expression::BranchingAndJumping(nodes::BranchingAndJumping::Call(nodes::Call {
+                target: relative(&["print"]),
+                arguments: CallArguments {
+                    type_arguments: vec![],
+                    arguments: vec![ValueArgument {
+                        label: ArgumentLabel::Positional,
+                        value: Expression::Symbol(relative(&["y"])),
+                    }],
+                },
+            })),
+        );
+    }
+
+    #[test]
+    fn recovering_from_a_broken_item() {
+        let file = test_parser("package main\nfun f(1) {\n}\nfun g() { }")
+            .parse_recovering()
+            .unwrap();
+        let items = file.package.package.items;
+
+        assert_eq!(items.len(), 2);
+        assert!(matches!(items[0], Item::Error(..)));
+        assert!(matches!(items[1], Item::Fun(..)));
+    }
+
+    #[test]
+    fn syntax_parameters_with_token_trees() {
+        let methods = parse_class_methods(
+            "
+            class Macros {
+                fun public unless(syntax body { a + b }) { }
+                fun public typed(syntax pipeline AstPipeline) { }
+            }
+            ",
+        );
+        let parameters = methods
+            .into_iter()
+            .flat_map(|method| method.r#abstract.signature.value_parameters)
+            .collect::<Vec<_>>();
+
+        assert_eq!(parameters.len(), 2);
+        assert!(parameters.iter().all(|parameter| parameter.is_syntax));
+        assert_eq!(
+            parameters[0].pattern.item,
+            PatternItem::Identifier(Identifier::from("body")),
+        );
+        assert_eq!(
+            parameters[0].token_tree,
+            Some(vec![
+                Token::Grouping(Grouping::OpenBrace),
+                Token::Identifier(Identifier::from("a")),
+                Token::OverloadableInfixOperator(OverloadableInfixOperator::Add),
+                Token::Identifier(Identifier::from("b")),
+                Token::Grouping(Grouping::CloseBrace),
+            ]),
+        );
+        assert_eq!(parameters[0].type_annotation, None);
+        assert_eq!(parameters[1].token_tree, None);
+        assert_eq!(
+            parameters[1].type_annotation,
+            Some(TypeReference::new(relative(&["AstPipeline"]))),
+        );
+    }
+
+    #[test]
+    fn mismatched_token_tree_brackets() {
+        for source in &[
+            "fun f(syntax b ( a ] ) { }",
+            "fun f(syntax b { [ a } ]) { }",
+        ] {
+            assert_described_error(test_parser(source).parse_fun(), "brackets must be balanced");
+        }
+    }
+
+    #[test]
+    fn lexer_errors_mid_file() {
+        let assert_lexer_error = |result: Result<()>| match result {
+            Err(Error::Lexer(err)) => assert!(format!("{:?}", err).contains("invalid escape")),
+            other => panic!("expected a lexer error but got {:?}", other),
+        };
+        let source = "package main\nvar x = f(1, \"\\q\", 2)\nvar y = 2";
+
+        assert_lexer_error(
+            test_parser("f(1, \"\\q\", 2)")
+                .parse_expression()
+                .map(|_| ()),
+        );
+        assert_lexer_error(test_parser(source).parse().map(|_| ()));
+        assert_lexer_error(test_parser(source).parse_recovering().map(|_| ()));
+    }
 }
@@ -21,8 +21,10 @@ pub struct ModifierSets {
     pub function: HashSet<Modifier>,
     pub method: HashSet<Modifier>,
     pub binding: HashSet<Modifier>,
+    pub extern_binding: HashSet<Modifier>,
     pub field: HashSet<Modifier>,
     pub class_extension: HashSet<Modifier>,
+    pub reader: HashSet<Modifier>,
 }
 
 pub struct AccessibilityModifierExtractor {
@@ -33,11 +35,11 @@ impl AccessibilityModifierExtractor {
     pub fn new() -> Self {
         let mut accessibility_tokens = HashMap::new();
         accessibility_tokens.insert(
-            Modifier::Accessibility(Accessibility::Private),
+            Modifier::Accessibility(Accessibility::Public),
             Accessibility::Public,
         );
         accessibility_tokens.insert(
-            Modifier::Accessibility(Accessibility::Private),
+            Modifier::Accessibility(Accessibility::Internal),
             Accessibility::Internal,
         );
         Self {
@@ -45,6 +47,9 @@ impl AccessibilityModifierExtractor {
         }
     }
 
+    /// Without an accessibility modifier, declarations are private. That goes
+    /// for packages too, except the main package which is always public as
+    /// the program's entry point.
     pub fn extract_accessibility_modifier(
         &self,
         modifiers: &HashSet<Modifier>,
@@ -75,8 +80,10 @@ impl Default for ModifierSets {
             function: new_function_modifier_set(),
             method: new_method_modifier_set(),
             binding: new_binding_modifier_set(),
+            extern_binding: new_extern_binding_modifier_set(),
             field: new_field_modifier_set(),
             class_extension: new_class_extension_modifier_set(),
+            reader: new_reader_modifier_set(),
         }
     }
 }
@@ -136,10 +143,18 @@ fn new_binding_modifier_set() -> HashSet<Modifier> {
     set.extend(vec![
         Modifier::Accessibility(Accessibility::Public),
         Modifier::Accessibility(Accessibility::Internal),
+        Modifier::Volatile,
+        Modifier::Nonvolatile,
     ]);
     set
 }
 
+fn new_extern_binding_modifier_set() -> HashSet<Modifier> {
+    let mut set = HashSet::new();
+    set.extend(vec![Modifier::Volatile, Modifier::Nonvolatile]);
+    set
+}
+
 fn new_field_modifier_set() -> HashSet<Modifier> {
     let mut set = HashSet::new();
     set.extend(vec![
@@ -157,3 +172,12 @@ fn new_class_extension_modifier_set() -> HashSet<Modifier> {
     ]);
     set
 }
+
+fn new_reader_modifier_set() -> HashSet<Modifier> {
+    let mut set = HashSet::new();
+    set.extend(vec![
+        Modifier::Accessibility(Accessibility::Public),
+        Modifier::Accessibility(Accessibility::Internal),
+    ]);
+    set
+}
@@ -42,14 +42,24 @@
 //!
 //! The parser doesn't care, since refuttabillity can only be asserted with a
 //! type system. Thus, they are both just "patterns" here.
+//!
+//! Nodes don't carry their source spans themselves. Instead, a parser can be
+//! asked to record the span of each item and expression alongside the main
+//! file it parses, which tooling such as a language server can search with
+//! [node_at], e.g. for hovers.
 
+use std::cmp::Reverse;
+use std::hash::{Hash, Hasher};
 use std::rc::Rc;
 
 use crate::common::multiphase::{
-    Accessibility, Identifier, InterpolatedString, Number, OverloadableInfixOperator,
-    PostfixOperator, PseudoIdentifier, Shebang, SyDoc, SylanString,
+    Accessibility, Identifier, Number, OverloadableInfixOperator, PostfixOperator,
+    PseudoIdentifier, Shebang, SyDoc, SylanString,
 };
 use crate::common::version::Version;
+use crate::lexing::tokens::Token;
+use crate::parsing::ParserError;
+use crate::source::Position;
 
 /// Shebangs and source versions are special, which is why they're outside of
 /// the `PackageFile` in which all other items and expressions reside. Both
@@ -70,6 +80,47 @@ pub struct MainFile {
     pub shebang: Option<Shebang>,
     pub version: Option<Version>,
     pub package: MainPackage,
+
+    /// Only recorded when parsing with `Parser::with_spans`, as each holds a
+    /// copy of its node.
+    pub spans: Vec<SpannedNode>,
+}
+
+/// Where a node was written, from the start of its first token up to, but
+/// not including, the end of its last.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn contains(&self, position: Position) -> bool {
+        let position = position.line_and_column();
+        (self.start.line_and_column() <= position) && (position < self.end.line_and_column())
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct SpannedNode {
+    pub span: Span,
+    pub node: Node,
+}
+
+/// Find the tightest node covering a position, i.e. the innermost item or
+/// expression written around it. Between two tokens, that is whichever node
+/// encloses both. Nothing is found in a file parsed without recording spans.
+pub fn node_at(file: &MainFile, position: Position) -> Option<&Node> {
+    file.spans
+        .iter()
+        .filter(|spanned| spanned.span.contains(position))
+        .min_by_key(|spanned| {
+            (
+                Reverse(spanned.span.start.line_and_column()),
+                spanned.span.end.line_and_column(),
+            )
+        })
+        .map(|spanned| &spanned.node)
 }
 
 // Packages only have items at top-level, with the exception of the main package that can also have
@@ -107,6 +158,7 @@ pub enum Item {
     Package(Package),
     Type(Type),
     Macro(Macro),
+    Annotated(Annotated),
 
     // Unlike the previous variants, these can be arbitrarily nested within
     // expressions. This is to allow corecursion among other features.
@@ -114,7 +166,34 @@ pub enum Item {
     // For loops also create bindings, but are not items because I can't
     // think of a use case for mutually recursive loop reiteration bindings.
     Var(Binding),
-    Final(Binding),
+    Final(Final),
+
+    Error(ParseFailure),
+}
+
+/// An item preceded by annotations, such as `@inline` or `@route("/path")`,
+/// which attach metadata for macros and tooling to consume.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Annotated {
+    pub annotations: Vec<Annotation>,
+    pub item: Box<Item>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Annotation {
+    pub symbol: Symbol,
+    pub arguments: Vec<ValueArgument>,
+}
+
+/// A region that failed to parse, standing in for the item or expression
+/// that should have been there so that a best-effort tree can be returned
+/// despite errors. It spans from where the failed parse started to the start
+/// of the last token skipped to recover.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct ParseFailure {
+    pub start: Position,
+    pub end: Position,
+    pub error: ParserError,
 }
 
 /// The expressions that allow Turing-complete computations, i.e. allowing
@@ -122,28 +201,62 @@ pub enum Item {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Expression {
     BranchingAndJumping(BranchingAndJumping),
-    Context(Block),
+    Break(Break),
+    Context(Context),
+    Continue(Continue),
+    Error(ParseFailure),
     Literal(Literal),
+    MemberAccess(MemberAccess),
     Operator(Operator),
+    RecordLiteral(RecordLiteral),
     Symbol(Symbol),
     Throw(Throw),
+
+    /// Comma-separated expressions in square brackets, such as `[1, 2, 3]`.
+    ListLiteral(Vec<Expression>),
+
+    /// Two or more comma-separated expressions in parentheses, such as
+    /// `(1, 2)`. A single one is merely grouped.
+    Tuple(Vec<Expression>),
     Use(Use),
+    Yield(Yield),
+
+    /// Turns a method into a function taking its receiver first, such as
+    /// `:toString` in `list.map(:toString)`.
     MemberHandle(Symbol),
     NonDestructiveUpdate(ExpressionCall),
     ReaderMacroActivation(ReaderMacroActivation),
 }
 
+/// Accessing a member on the value yielded by an arbitrary expression, such as
+/// `this.field` or `origin().x`. Chains of plain identifiers like `a.b.c` are
+/// instead parsed as a [Symbol], as they can be resolved statically.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct MemberAccess {
+    pub target: Box<Expression>,
+    pub member: Identifier,
+}
+
+/// A record built from labelled fields, such as `(x: 1, y: 2)`. A call to a
+/// capitalised symbol with only labelled arguments, such as
+/// `Point(x: 1, y: 2)`, is a record literal of that type.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct RecordLiteral {
+    pub record_type: Option<TypeReference>,
+    pub fields: Vec<ValueArgument>,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Slice {
-    pub start: Option<Number>,
-    pub step: Option<Number>,
-    pub end: Option<Number>,
+    pub start: Option<Expression>,
+    pub step: Option<Expression>,
+    pub end: Option<Expression>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum SliceFragment {
     Ellipsis,
-    Slice(Slice),
+    Slice(Box<Slice>),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -151,9 +264,25 @@ pub struct MultiSlice(pub Vec<SliceFragment>);
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Operator {
-    MultiSlice(MultiSlice),
+    /// Invokes the left invocable and then the right one on its result.
+    Compose(Box<Expression>, Box<Expression>),
+
+    /// Indexes into the left expression, such as `array[0]`.
+    Index(Box<Expression>, Box<Expression>),
+
+    /// Slices the left expression, such as `array[|1 : 2|]`.
+    MultiSlice(Box<Expression>, MultiSlice),
+
     OverloadableInfix(Box<Expression>, OverloadableInfixOperator, Box<Expression>),
     Postfix(Box<Expression>, PostfixOperator),
+    Prefix(PrefixOperator, Box<Expression>),
+}
+
+/// Prefix operators only exist in the parser; the lexer cannot tell a prefix
+/// `-` from an infix one.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum PrefixOperator {
+    Negate,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -182,6 +311,7 @@ pub enum BranchingAndJumping {
 pub struct FunModifiers {
     pub accessibility: Accessibility,
     pub is_extern: bool,
+    pub is_generator: bool,
     pub is_operator: bool,
 }
 
@@ -197,11 +327,17 @@ pub struct ValueParameter {
     /// The same applies to lambdas and enum variants.
     pub label: Option<Identifier>,
 
-    /// TODO: tolerate any token or grouped token to tolerate procedural macros.
     pub is_syntax: bool,
 
+    /// A `syntax` parameter can be followed by a group of tokens rather than
+    /// a type, which is kept unparsed for a procedural macro to interpret.
+    pub token_tree: Option<Vec<Token>>,
+
     pub pattern: Pattern,
-    pub type_annotation: TypeReference,
+
+    /// Only absent for a `syntax` parameter followed by a token tree.
+    pub type_annotation: Option<TypeReference>,
+
     pub default_value: Option<Expression>,
     pub sydoc: Option<SyDoc>,
 }
@@ -221,8 +357,8 @@ pub struct ClassValueParameter {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ReturnType {
-    r#type: TypeReference,
-    ignorable: bool,
+    pub r#type: TypeReference,
+    pub ignorable: bool,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -235,6 +371,27 @@ pub struct FunSignature {
     // Unlike lambdas, an empty return type does not fallback to inference.
     // Instead, `Void` is assumed.
     pub return_type: Option<ReturnType>,
+
+    /// Operator funs are named by the operator they overload, such as
+    /// `fun public operator + (that This) This { }`. Their name is then the
+    /// operator as written, and this records which one it is.
+    pub operator: Option<OverloadedOperator>,
+}
+
+/// The slice operators are told apart by how much of the slice notation their
+/// overloads support; see `multiphase::OverloadableSliceOperator`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum OverloadedOperator {
+    Infix(OverloadableInfixOperator),
+
+    /// `[||]`
+    Index,
+
+    /// `[|:|]`
+    Slice,
+
+    /// `[|:...|]`
+    MultiSlice,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -248,6 +405,10 @@ pub struct Fun {
 pub struct ImportSingleStem {
     pub name: Identifier,
 
+    /// Set with a trailing `as`, allowing items with clashing names from
+    /// different packages to be imported alongside each other.
+    pub alias: Option<Identifier>,
+
     // Will be empty for the vast majority of imports.
     pub readers: Vec<Symbol>,
 }
@@ -256,6 +417,10 @@ pub struct ImportSingleStem {
 pub enum ImportStem {
     Single(ImportSingleStem),
     Multiple(Vec<Import>),
+
+    /// Written `if v2 { ... }` in an import list, these imports only apply if
+    /// the file declares at least that source version.
+    VersionGuarded(Version, Vec<Import>),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -305,10 +470,25 @@ pub struct Class {
 ///
 /// Labels can still be used, however.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
-pub struct EnumVariant {
+pub struct EnumVariantParameter {
     pub label: Option<Identifier>,
     pub name: Identifier,
     pub type_annotation: TypeReference,
+}
+
+/// Variants can be given explicit integer discriminants, e.g. for FFI and
+/// serialisation. Those without one take the previous variant's discriminant
+/// plus one, starting from zero.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct EnumVariant {
+    pub name: Identifier,
+    pub parameters: Vec<EnumVariantParameter>,
+    pub discriminant: Option<Expression>,
+
+    /// The discriminant the variant ends up with, whether explicit or
+    /// auto-assigned.
+    pub discriminant_value: i64,
+
     pub sydoc: Option<SyDoc>,
 }
 
@@ -343,10 +523,34 @@ pub struct Type {
     pub sydoc: Option<SyDoc>,
 }
 
+/// A primitive built into the language, which a type reference might resolve
+/// to instead of a user-defined type.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PrimitiveType {
+    Integer { signed: bool, bits: u8 },
+}
+
+impl PrimitiveType {
+    pub fn signed(bits: u8) -> Self {
+        PrimitiveType::Integer { signed: true, bits }
+    }
+
+    pub fn unsigned(bits: u8) -> Self {
+        PrimitiveType::Integer {
+            signed: false,
+            bits,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct TypeReference {
     pub symbol: Symbol,
     pub type_arguments: Vec<TypeArgument>,
+
+    /// Set if the symbol names a known primitive type, as opposed to a
+    /// user-defined one.
+    pub primitive: Option<PrimitiveType>,
 }
 
 impl TypeReference {
@@ -354,6 +558,7 @@ impl TypeReference {
         Self {
             symbol,
             type_arguments: vec![],
+            primitive: None,
         }
     }
 }
@@ -367,10 +572,20 @@ pub struct Extension {
     pub sydoc: Option<SyDoc>,
 }
 
+/// The interface method that an `override` claims to specialise, identified
+/// by its name and the labels its parameters are passed with. Interfaces can
+/// be declared in other files, so checking that one of the class's
+/// implemented interfaces really declares it is left to a later phase.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct OverrideTarget {
+    pub name: Identifier,
+    pub labels: Vec<Option<Identifier>>,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct MethodModifiers {
-    fun_modifiers: FunModifiers,
-    overrides: bool,
+    pub fun_modifiers: FunModifiers,
+    pub overrides: Option<OverrideTarget>,
 }
 
 /// Methods and just bindings in a class, which can be potentially abstract (i.e. with no initial
@@ -394,8 +609,8 @@ pub struct AbstractMethod {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ConcreteMethod {
-    r#abstract: AbstractMethod,
-    scope: Block,
+    pub r#abstract: AbstractMethod,
+    pub scope: Block,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -415,9 +630,23 @@ pub struct TypeParameter {
     pub sydoc: Option<SyDoc>,
 }
 
+/// How the caller labelled an argument, if at all.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ArgumentLabel {
+    Labelled(Identifier),
+
+    /// Passing `_` as the label, e.g. `f(_: x)`, states that the argument is
+    /// positional even where a label would otherwise be expected. The
+    /// caller's intent is kept for checking it against the parameters later.
+    Unlabelled,
+
+    /// No label was written at all.
+    Positional,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Argument<T> {
-    pub label: Option<Identifier>,
+    pub label: ArgumentLabel,
     pub value: T,
 }
 
@@ -431,7 +660,15 @@ pub type ValueArgument = Argument<Expression>;
 /// positional or keyword arguments; unlike other languages it is the choice of
 /// the caller rather than the definer. If passed as a keyword argument, an
 /// identifier is carried with it in the parse tree.
-pub type TypeArgument = Argument<TypeReference>;
+pub type TypeArgument = Argument<TypeArgumentValue>;
+
+/// A type argument can be a `_` wildcard, leaving inference to fill it in
+/// like an argument hole, e.g. `List[_]`.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum TypeArgumentValue {
+    Given(TypeReference),
+    Wildcard,
+}
 
 // Sylan's "symbol tables" are just a collection of bindings in the current
 // scope. Parent scopes can be looked up to find bindings in outer closures,
@@ -455,15 +692,33 @@ pub type TypeArgument = Argument<TypeReference>;
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Binding {
     pub pattern: Pattern,
-    pub value: Box<Expression>,
+
+    /// Only absent for extern finals, whose values live in another compiled
+    /// artefact.
+    pub value: Option<Box<Expression>>,
+
     pub explicit_type_annotation: Option<TypeReference>,
 }
 
+/// Extern finals refer to variables in other compiled artefacts, which might
+/// change them behind Sylan's back. They are treated as volatile, with memory
+/// fences on each access, unless declared `nonvolatile` to promise that the
+/// other artefact keeps them constant.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Volatility {
+    Volatile,
+    Nonvolatile,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Final {
     pub accessibility: Accessibility,
     pub binding: Binding,
     pub sydoc: Option<SyDoc>,
+
+    /// Only extern finals have a volatility, as Sylan's own finals never
+    /// change.
+    pub volatility: Option<Volatility>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -472,11 +727,14 @@ pub enum MacroItem {
     Call(Call),
 }
 
+/// Declares a reader macro that takes over lexing whenever its trigger is
+/// next in the source, e.g. `reader public if = readIf for "if"`.
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct ReaderMacroDefinition {
-    name: Identifier,
-    r#macro: Symbol,
-    trigger: SylanString,
+    pub accessibility: Accessibility,
+    pub name: Identifier,
+    pub r#macro: Symbol,
+    pub trigger: SylanString,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -494,6 +752,7 @@ pub struct Field {
     pub is_extern: bool,
     pub accessibility: Accessibility,
     pub binding: Binding,
+    pub sydoc: Option<SyDoc>,
 }
 
 /// Expressions are seperate from bindings.
@@ -521,19 +780,45 @@ type Expressions = Vec<Expression>;
 /// declare new types like packages can.
 ///
 /// All functions, concrete methods, and lambdas have an attached scope.
-
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+///
+/// A block yields the value of its final expression, which is kept apart as
+/// the tail; the other expressions are evaluated only for their effects.
+///
+/// Equality and hashing consider only a block's own contents, not its parent.
+/// Otherwise each would walk every ancestor, and would never finish once scopes
+/// can form cycles.
+#[derive(Clone, Debug)]
 pub struct Block {
     pub bindings: Vec<Binding>,
     pub expressions: Expressions,
+    pub tail: Option<Box<Expression>>,
     pub parent: Option<Rc<Block>>,
 }
 
+impl PartialEq for Block {
+    fn eq(&self, other: &Self) -> bool {
+        (self.bindings == other.bindings)
+            && (self.expressions == other.expressions)
+            && (self.tail == other.tail)
+    }
+}
+
+impl Eq for Block {}
+
+impl Hash for Block {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bindings.hash(state);
+        self.expressions.hash(state);
+        self.tail.hash(state);
+    }
+}
+
 impl Block {
     pub fn new_root() -> Self {
         Block {
             bindings: vec![],
             expressions: vec![],
+            tail: None,
             parent: None,
         }
     }
@@ -542,6 +827,7 @@ impl Block {
         Block {
             bindings: vec![],
             expressions: vec![],
+            tail: None,
             parent: Some(parent.clone()),
         }
     }
@@ -551,12 +837,21 @@ impl Block {
 pub struct LambdaValueParameter {
     pub label: Option<Identifier>,
     pub pattern: Pattern,
+
+    // Only parenthesised parameter lists can annotate their types; they are
+    // otherwise inferred.
+    pub explicit_type_annotation: Option<TypeReference>,
+
     pub default_value: Option<Expression>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct LambdaSignature {
+    pub type_parameters: Vec<TypeParameter>,
     pub value_parameters: Vec<LambdaValueParameter>,
+
+    // Boxed as lambdas are literals, which are embedded in patterns.
+    pub explicit_return_type_annotation: Option<Box<TypeReference>>,
     // Non-void lambda results can always be ignored without warnings, hence no
     // `ignorable` modifier. Sylan is only concerned if declared top-level
     // functions in an API are ignored without declaring such an ignoral to be
@@ -586,18 +881,32 @@ pub enum Symbol {
     Relative(SymbolLookup),
     Absolute(SymbolLookup),
     Pseudo(PseudoIdentifier),
+
+    /// A lookup rooted at a pseudo-identifier that stands for a namespace
+    /// rather than a value, such as `this.package.config` or `super.toString`.
+    PseudoLookup(PseudoIdentifier, SymbolLookup),
+
     InferredEnumVariant(Identifier),
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum Literal {
+    ByteString(Vec<u8>),
     Char(char),
-    InterpolatedString(InterpolatedString),
+    InterpolatedString(Vec<InterpolatedStringPart>),
     Number(Number),
     String(SylanString),
     Lambda(Lambda),
 }
 
+/// An interpolated string's literal text and interpolations in source order,
+/// ready for the backend to glue together. Empty fragments are left out.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum InterpolatedStringPart {
+    Fragment(String),
+    Interpolation(Box<Expression>),
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Switch {
     pub expression: Box<Expression>,
@@ -673,6 +982,10 @@ pub struct Cond(pub Vec<CondCase>);
 pub struct CaseMatch {
     pub pattern: Pattern,
     pub guard: Option<Expression>,
+
+    // Set for cases like `Dog as dog`, which only match values of that type and
+    // bind them at it.
+    pub narrowed_type: Option<TypeReference>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
@@ -704,6 +1017,32 @@ pub struct For {
     pub reiteration_symbol: Option<Identifier>,
 }
 
+/// A `with` block, optionally labelled so that `break` can exit it early.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Context {
+    pub label: Option<Identifier>,
+    pub scope: Block,
+}
+
+/// Breaking out of the enclosing `for` loop or `with` block with the given
+/// label, which can be several loops out, yielding the value as its result.
+/// Without a label, the innermost loop is broken out of; checking that there
+/// is one is left to a later phase.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Break {
+    pub label: Option<Identifier>,
+    pub value: Option<Box<Expression>>,
+}
+
+/// Continuing a `for` loop, with the arguments becoming the values of its
+/// bindings on the next iteration. Without a label, the innermost loop is
+/// continued; with one, the loop whose reiteration symbol matches it is.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Continue {
+    pub label: Option<Identifier>,
+    pub arguments: Vec<ValueArgument>,
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct While {
     pub condition: Box<Expression>,
@@ -727,6 +1066,12 @@ pub struct WhileVar {
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Throw(pub Box<Expression>);
 
+/// Yielding a value from a generator fun, suspending it until the next value
+/// is asked for. It can only be used directly within a `gen fun`, not within
+/// lambdas inside one.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Yield(pub Box<Expression>);
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct PatternGetter {
     pub name: Identifier,
@@ -753,6 +1098,9 @@ pub enum PatternItem {
     // Irrefutable if all fields are also irefuttable.
     Composite(CompositePattern),
 
+    // Irrefutable if all elements are also irrefuttable.
+    Tuple(Vec<Pattern>),
+
     // Refuttable, as it's worked out at runtime from what the symbol resolves
     // to. Irrefuttable if it can be resolved at compile-time _and_ the
     // left-hand side can also be resolved at compile-time.
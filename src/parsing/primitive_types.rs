@@ -0,0 +1,33 @@
+//! Some type names refer to primitives built into the language rather than
+//! user-defined types. Recognising them while parsing lets later phases treat
+//! them specially, such as checking that a literal assigned to an `Int8`
+//! actually fits into eight bits.
+//!
+//! Aliases like `Byte` and `Long` map onto the same primitives as their
+//! explicitly-sized counterparts, canonicalising them. `Int` and `UInt` are
+//! not in the table, as they are not fixed-width.
+
+use std::collections::HashMap;
+
+use crate::parsing::nodes::PrimitiveType;
+
+pub fn new() -> HashMap<&'static str, PrimitiveType> {
+    let mut map = HashMap::new();
+    map.extend(vec![
+        ("Int8", PrimitiveType::signed(8)),
+        ("Int16", PrimitiveType::signed(16)),
+        ("Int32", PrimitiveType::signed(32)),
+        ("Int64", PrimitiveType::signed(64)),
+        ("UInt8", PrimitiveType::unsigned(8)),
+        ("UInt16", PrimitiveType::unsigned(16)),
+        ("UInt32", PrimitiveType::unsigned(32)),
+        ("UInt64", PrimitiveType::unsigned(64)),
+        //
+        // Aliases
+        //
+        ("Byte", PrimitiveType::unsigned(8)),
+        ("Short", PrimitiveType::signed(16)),
+        ("Long", PrimitiveType::signed(64)),
+    ]);
+    map
+}
@@ -26,7 +26,7 @@ impl<'a> Index<usize> for CharReadMany<'a> {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Position {
     /// Suitable for calculating offsets in a lexer.
     absolute_character_index: usize,
@@ -38,6 +38,23 @@ pub struct Position {
 }
 
 impl Position {
+    /// A position known only by its line and column, such as one sent by an
+    /// editor. Its absolute index is unknown, so it is only meaningful to
+    /// compare it by line and column.
+    pub fn at(line: usize, column: usize) -> Self {
+        Self {
+            absolute_character_index: 0,
+            character_position_in_line: column,
+            line,
+        }
+    }
+
+    /// The line and column, both starting from one, which order positions
+    /// within the same source.
+    pub fn line_and_column(&self) -> (usize, usize) {
+        (self.line, self.character_position_in_line)
+    }
+
     fn character_position(&self) -> usize {
         self.absolute_character_index + 1
     }
@@ -47,7 +64,9 @@ impl Position {
         self.line += 1;
     }
 
-    fn update_all(&mut self, chars: CharReadMany<'_>) {
+    /// Tabs advance the position in the line by `tab_width` rather than one,
+    /// so that columns line up with how tab-indented code is displayed.
+    fn update_all(&mut self, chars: CharReadMany<'_>, tab_width: usize) {
         let mut skip_next = false;
         let CharReadMany(char_slice) = chars;
         for (index, current) in char_slice.iter().enumerate() {
@@ -62,6 +81,10 @@ impl Position {
                 }
                 if newline.is_some() {
                     self.increment_position_line()
+                } else if *current == '\t' {
+                    self.character_position_in_line += tab_width;
+                } else {
+                    self.character_position_in_line += 1;
                 }
             }
         }
@@ -90,6 +113,25 @@ mod tests {
         Source::from(source_chars)
     }
 
+    #[test]
+    fn column_tracking() {
+        let mut source = test_source("\tab\ncd");
+        source.discard_many(3);
+        assert_eq!(source.position.character_position_in_line, 4);
+        source.discard_many(2);
+        assert_eq!(source.position.line, 2);
+        assert_eq!(source.position.character_position_in_line, 2);
+    }
+
+    #[test]
+    fn configured_tab_width() {
+        let mut source = test_source("\tab").with_tab_width(4);
+        source.discard();
+        assert_eq!(source.position.character_position_in_line, 5);
+        source.discard();
+        assert_eq!(source.position.character_position_in_line, 6);
+    }
+
     #[test]
     fn position_tracking() {
         let test_line = "test line";
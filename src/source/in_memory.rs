@@ -13,12 +13,28 @@ use crate::source::{CharReadMany, Position};
 pub struct Source {
     content: Vec<char>,
     pub position: Position,
+    tab_width: usize,
 }
 
 impl Source {
     pub fn at_start(&self) -> bool {
         self.position.absolute_character_index == 0
     }
+
+    /// The source text from `start` up to the current position, exactly as
+    /// written.
+    pub fn text_since(&self, start: Position) -> String {
+        self.content[start.absolute_character_index..self.position.absolute_character_index]
+            .iter()
+            .collect()
+    }
+
+    /// How many columns a tab advances the position by, which is one by
+    /// default.
+    pub fn with_tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
 }
 
 impl From<Vec<char>> for Source {
@@ -26,6 +42,7 @@ impl From<Vec<char>> for Source {
         Self {
             content,
             position: Default::default(),
+            tab_width: 1,
         }
     }
 }
@@ -47,7 +64,8 @@ impl<'a> PeekableBuffer<'a, char, CharReadMany<'a>> for Source {
         } else {
             let new_position = self.position.absolute_character_index + n;
             let result = &self.content[self.position.absolute_character_index..new_position];
-            self.position.update_all(CharReadMany(result));
+            self.position
+                .update_all(CharReadMany(result), self.tab_width);
             let chars = CharReadMany(result);
             Some(chars)
         }
@@ -60,7 +78,8 @@ impl<'a> PeekableBuffer<'a, char, CharReadMany<'a>> for Source {
             let new_position = self.position.absolute_character_index + n;
             let result =
                 &self.content[self.position.absolute_character_index..new_position].to_vec();
-            self.position.update_all(CharReadMany(result));
+            self.position
+                .update_all(CharReadMany(result), self.tab_width);
             true
         }
     }